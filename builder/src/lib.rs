@@ -10,3 +10,12 @@ pub fn derive(input: TokenStream) -> TokenStream {
         Err(e) => e.to_compile_error().into(),
     }
 }
+
+#[proc_macro_attribute]
+pub fn builder(args: TokenStream, input: TokenStream) -> TokenStream {
+    let item_fn = syn::parse_macro_input!(input as syn::ItemFn);
+    match struct_generator::generate_fn(args.into(), &item_fn) {
+        Ok(token_stream) => token_stream.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
@@ -0,0 +1,310 @@
+use syn::parse::Parser;
+
+// What a key takes, checked up front so that a malformed value is reported before anything reads it
+#[derive(Clone, Copy)]
+pub(crate) enum AttrValue {
+    // `key`
+    Flag,
+    // `key = "..."`, any string
+    Str,
+    // `key = "name"`, naming something that gets generated
+    Ident,
+    // `key = "with_"`, put in front of names to make identifiers out of them
+    IdentPrefix,
+    // `key = "expr"`
+    Expr,
+    // `key = "pub(crate)"`
+    Vis,
+    // `key = "a"`, one of the given strings
+    OneOf(&'static [&'static str]),
+    // `key = path` or `key = "path"`
+    Path,
+    // `key` or `key = "expr"`
+    FlagOrExpr,
+    // `key(...)`, passed through as given and allowed more than once
+    List,
+    // `key(a, b = "...")`, checked against its own keys
+    Nested(&'static [(&'static str, AttrValue)]),
+}
+
+// keys accepted inside `#[builder(...)]` on the struct itself
+pub(crate) const STRUCT_ATTR_KEYS: &[(&str, AttrValue)] = &[
+    ("typestate", AttrValue::Flag),
+    ("default", AttrValue::Flag),
+    ("error", AttrValue::Path),
+    ("pattern", AttrValue::OneOf(&["owned", "mutable", "immutable"])),
+    ("validate", AttrValue::Path),
+    ("vis", AttrValue::Vis),
+    ("to_builder", AttrValue::Flag),
+    ("derive", AttrValue::List),
+    ("env_prefix", AttrValue::Str),
+    ("name", AttrValue::Ident),
+    ("setter_prefix", AttrValue::IdentPrefix),
+    ("build_fn", AttrValue::Nested(BUILD_FN_ATTR_KEYS)),
+    ("constructor", AttrValue::Ident),
+    ("struct_attr", AttrValue::List),
+];
+// keys accepted inside `#[builder(...)]` on a field
+pub(crate) const FIELD_ATTR_KEYS: &[(&str, AttrValue)] = &[
+    ("each", AttrValue::Ident),
+    ("default", AttrValue::FlagOrExpr),
+    ("validate", AttrValue::Path),
+    ("setter", AttrValue::Nested(SETTER_ATTR_KEYS)),
+    ("vis", AttrValue::Vis),
+    ("private", AttrValue::Flag),
+    ("name", AttrValue::Ident),
+    ("sub_builder", AttrValue::Flag),
+    ("merge", AttrValue::OneOf(&["append", "replace"])),
+    ("env_separator", AttrValue::Str),
+    ("env_skip", AttrValue::Flag),
+    ("try_setter", AttrValue::Flag),
+    ("skip", AttrValue::Flag),
+    ("computed", AttrValue::Expr),
+    ("optional", AttrValue::Flag),
+    ("required", AttrValue::Flag),
+];
+// keys accepted inside `#[builder(setter(...))]` on a field
+pub(crate) const SETTER_ATTR_KEYS: &[(&str, AttrValue)] = &[("into", AttrValue::Flag), ("name", AttrValue::Ident)];
+// keys accepted inside `#[builder(build_fn(...))]` on the struct
+pub(crate) const BUILD_FN_ATTR_KEYS: &[(&str, AttrValue)] = &[("name", AttrValue::Ident)];
+
+type NestedMetas = syn::punctuated::Punctuated<syn::Meta, syn::Token![,]>;
+
+// #[builder(a, b = "..")] #[builder(c)] => [a, b = "..", c]
+fn get_builder_metas(attrs: &[syn::Attribute]) -> syn::Result<Vec<syn::Meta>> {
+    let mut metas = vec![];
+    for attr in attrs {
+        if let syn::Meta::List(list) = &attr.meta {
+            if list.path.is_ident("builder") {
+                let nested_metas = NestedMetas::parse_terminated.parse2(list.tokens.clone())?;
+                metas.extend(nested_metas);
+            }
+        }
+    }
+    Ok(metas)
+}
+
+// Levenshtein distance, used to point a mistyped key at the one that was most likely meant
+fn get_edit_distance(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut distances: Vec<usize> = (0..=b_chars.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut previous_diagonal = distances[0];
+        distances[0] = i + 1;
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let substitution = previous_diagonal + usize::from(a_char != *b_char);
+            previous_diagonal = distances[j + 1];
+            distances[j + 1] = substitution.min(distances[j] + 1).min(previous_diagonal + 1);
+        }
+    }
+    distances[b_chars.len()]
+}
+
+// `each` with `AttrValue::Ident` => `each = "..."`, how the key is meant to be written
+fn get_key_usage(key: &str, value: AttrValue) -> String {
+    match value {
+        AttrValue::Flag => key.to_string(),
+        AttrValue::Str | AttrValue::Ident | AttrValue::IdentPrefix | AttrValue::Expr | AttrValue::Vis => format!(r#"{} = "...""#, key),
+        AttrValue::OneOf(options) => format!("{} = {}", key, options.iter().map(|option| format!(r#""{}""#, option)).collect::<Vec<_>>().join(" | ")),
+        AttrValue::Path => format!("{} = path", key),
+        AttrValue::FlagOrExpr => format!(r#"{}` or `{} = "...""#, key, key),
+        AttrValue::List | AttrValue::Nested(_) => format!("{}(...)", key),
+    }
+}
+
+fn get_unknown_key_error(outer_key: &str, path: &syn::Path, allowed_keys: &[(&str, AttrValue)]) -> syn::Error {
+    let key = quote::quote!(#path).to_string().replace(' ', "");
+    // as lenient as rustc's own suggestions: a third of the key may be mistyped, or its letters swapped around
+    let get_sorted_chars = |key: &str| {
+        let mut chars: Vec<char> = key.chars().collect();
+        chars.sort_unstable();
+        chars
+    };
+    let closest_key = allowed_keys
+        .iter()
+        .map(|(allowed_key, value)| (get_edit_distance(&key, allowed_key), *allowed_key, *value))
+        .min_by_key(|(distance, _, _)| *distance)
+        .filter(|(distance, allowed_key, _)| *distance <= key.len().max(3) / 3 || get_sorted_chars(&key) == get_sorted_chars(allowed_key));
+    let message = match closest_key {
+        Some((_, allowed_key, value)) => format!("unknown key `{}` in `{}(...)`, did you mean `{}`?", key, outer_key, get_key_usage(allowed_key, value)),
+        None => format!(
+            "unknown key `{}` in `{}(...)`, expected one of {}",
+            key,
+            outer_key,
+            allowed_keys.iter().map(|(allowed_key, _)| format!("`{}`", allowed_key)).collect::<Vec<_>>().join(", ")
+        ),
+    };
+    syn::Error::new_spanned(path, message)
+}
+
+fn check_str_value(lit_str: &syn::LitStr, key: &str, value: AttrValue) -> syn::Result<()> {
+    match value {
+        AttrValue::Ident => match lit_str.parse::<syn::Ident>() {
+            Ok(_) => Ok(()),
+            Err(_) => Err(syn::Error::new(lit_str.span(), format!("expected `{}` to be an identifier, found `{}`", key, lit_str.value()))),
+        },
+        // any name put after it must still be an identifier, `with_` + `host` is but `with-` + `host` isn't
+        AttrValue::IdentPrefix => match syn::parse_str::<syn::Ident>(&format!("{}name", lit_str.value())) {
+            Ok(_) if !lit_str.value().starts_with("r#") => Ok(()),
+            _ => Err(syn::Error::new(
+                lit_str.span(),
+                format!("expected `{}` to be the start of an identifier, found `{}`", key, lit_str.value()),
+            )),
+        },
+        AttrValue::Expr | AttrValue::FlagOrExpr => lit_str.parse::<syn::Expr>().map(|_| ()),
+        AttrValue::Vis => lit_str.parse::<syn::Visibility>().map(|_| ()),
+        AttrValue::Path => lit_str.parse::<syn::Path>().map(|_| ()),
+        AttrValue::OneOf(options) if !options.contains(&lit_str.value().as_str()) => Err(syn::Error::new(lit_str.span(), format!("expected `{}`", get_key_usage(key, value)))),
+        _ => Ok(()),
+    }
+}
+
+// Checks `metas`, which came out of `outer_key(...)`, against `allowed_keys`, pushing one error per problem
+fn check_metas(outer_key: &str, metas: NestedMetas, allowed_keys: &[(&str, AttrValue)], seen_keys: &mut Vec<String>, errors: &mut Vec<syn::Error>) {
+    for meta in metas {
+        let allowed = meta.path().get_ident().and_then(|ident| allowed_keys.iter().find(|(allowed_key, _)| ident == allowed_key));
+        let (key, value) = match allowed {
+            Some((key, value)) => (*key, *value),
+            None => {
+                errors.push(get_unknown_key_error(outer_key, meta.path(), allowed_keys));
+                continue;
+            }
+        };
+        if !matches!(value, AttrValue::List) {
+            if seen_keys.iter().any(|seen_key| seen_key == key) {
+                errors.push(syn::Error::new_spanned(meta.path(), format!("duplicate key `{}`, it can only be given once", key)));
+                continue;
+            }
+            seen_keys.push(key.to_string());
+        }
+        let wrong_value_error = syn::Error::new_spanned(&meta, format!("expected `{}`", get_key_usage(key, value)));
+        match (value, &meta) {
+            (AttrValue::Flag | AttrValue::FlagOrExpr, syn::Meta::Path(_)) | (AttrValue::List, syn::Meta::List(_)) => (),
+            (AttrValue::Nested(nested_keys), syn::Meta::List(list)) => match list.parse_args_with(NestedMetas::parse_terminated) {
+                Ok(nested_metas) => check_metas(key, nested_metas, nested_keys, &mut vec![], errors),
+                Err(err) => errors.push(err),
+            },
+            (AttrValue::Path, syn::Meta::NameValue(syn::MetaNameValue { value: syn::Expr::Path(_), .. })) => (),
+            (AttrValue::Flag | AttrValue::List | AttrValue::Nested(_), _) => errors.push(wrong_value_error),
+            (
+                _,
+                syn::Meta::NameValue(syn::MetaNameValue {
+                    value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit_str), .. }),
+                    ..
+                }),
+            ) => {
+                if let Err(err) = check_str_value(lit_str, key, value) {
+                    errors.push(err);
+                }
+            }
+            _ => errors.push(wrong_value_error),
+        }
+    }
+}
+
+// Every problem with the `#[builder(...)]` attrs among `attrs`: attrs that aren't a list, lists that don't parse,
+// unknown keys, values of the wrong kind and keys given twice. Each gets its own error, so that one compile shows them all.
+pub(crate) fn get_builder_attr_errors(attrs: &[syn::Attribute], allowed_keys: &[(&str, AttrValue)]) -> Vec<syn::Error> {
+    let mut errors = vec![];
+    let mut seen_keys = vec![];
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("builder")) {
+        match &attr.meta {
+            // unlike parsing the bare tokens, this points a missing value at the closing paren rather than the derive
+            syn::Meta::List(list) => match list.parse_args_with(NestedMetas::parse_terminated) {
+                Ok(metas) => check_metas("builder", metas, allowed_keys, &mut seen_keys, &mut errors),
+                Err(err) => errors.push(err),
+            },
+            meta => errors.push(syn::Error::new_spanned(meta, "expected `builder(...)`")),
+        }
+    }
+    errors
+}
+
+// [a, b] => a with b attached, so that both are reported
+pub(crate) fn combine_errors(errors: Vec<syn::Error>) -> syn::Result<()> {
+    let mut errors = errors.into_iter();
+    match errors.next() {
+        Some(mut error) => {
+            errors.for_each(|other| error.combine(other));
+            Err(error)
+        }
+        None => Ok(()),
+    }
+}
+
+// #[builder(typestate)] => true
+pub(crate) fn has_builder_attr_flag(attrs: &[syn::Attribute], key: &str) -> syn::Result<bool> {
+    Ok(get_builder_metas(attrs)?.iter().any(|meta| matches!(meta, syn::Meta::Path(path) if path.is_ident(key))))
+}
+
+// #[builder(each = "arg")] => "arg"
+pub(crate) fn get_builder_attr_str(attrs: &[syn::Attribute], key: &str) -> syn::Result<Option<syn::LitStr>> {
+    for meta in get_builder_metas(attrs)? {
+        if let syn::Meta::NameValue(kv) = meta {
+            if kv.path.is_ident(key) {
+                if let syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit_str), .. }) = kv.value {
+                    return Ok(Some(lit_str));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+// #[builder(error = path::to::MyError)] => path::to::MyError
+pub(crate) fn get_builder_attr_path(attrs: &[syn::Attribute], key: &str) -> syn::Result<Option<syn::Path>> {
+    for meta in get_builder_metas(attrs)? {
+        if let syn::Meta::NameValue(kv) = meta {
+            if kv.path.is_ident(key) {
+                match kv.value {
+                    syn::Expr::Path(expr_path) => return Ok(Some(expr_path.path)),
+                    syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit_str), .. }) => return Ok(Some(lit_str.parse()?)),
+                    _ => (),
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+// #[builder(setter(into))] with `key` = "setter" => [into]
+pub(crate) fn get_builder_attr_nested_metas(attrs: &[syn::Attribute], key: &str) -> syn::Result<Option<Vec<syn::Meta>>> {
+    for meta in get_builder_metas(attrs)? {
+        if let syn::Meta::List(list) = meta {
+            if list.path.is_ident(key) {
+                return Ok(Some(NestedMetas::parse_terminated.parse2(list.tokens)?.into_iter().collect()));
+            }
+        }
+    }
+    Ok(None)
+}
+
+// #[builder(struct_attr(a), struct_attr(b, c))] with `key` = "struct_attr" => [a, b, c]
+pub(crate) fn get_builder_attr_all_nested_metas(attrs: &[syn::Attribute], key: &str) -> syn::Result<Vec<syn::Meta>> {
+    let mut nested_metas = vec![];
+    for meta in get_builder_metas(attrs)? {
+        if let syn::Meta::List(list) = meta {
+            if list.path.is_ident(key) {
+                nested_metas.extend(NestedMetas::parse_terminated.parse2(list.tokens)?);
+            }
+        }
+    }
+    Ok(nested_metas)
+}
+
+// [into] => `has_nested_meta_flag(.., "into")` is true
+pub(crate) fn has_nested_meta_flag(metas: &[syn::Meta], key: &str) -> bool {
+    metas.iter().any(|meta| matches!(meta, syn::Meta::Path(path) if path.is_ident(key)))
+}
+
+// [name = "finish"] => `get_nested_meta_str(.., "name")` is "finish"
+pub(crate) fn get_nested_meta_str(metas: &[syn::Meta], key: &str) -> Option<syn::LitStr> {
+    metas.iter().find_map(|meta| match meta {
+        syn::Meta::NameValue(syn::MetaNameValue {
+            path,
+            value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit_str), .. }),
+            ..
+        }) if path.is_ident(key) => Some(lit_str.clone()),
+        _ => None,
+    })
+}
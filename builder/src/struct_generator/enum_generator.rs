@@ -0,0 +1,63 @@
+use syn::spanned::Spanned;
+
+use super::{builder_attrs, BuildTarget};
+
+// UserRenamed => user_renamed
+fn get_snake_case_name(ident: &syn::Ident) -> String {
+    let mut snake_case_name = String::new();
+    for (idx, ch) in syn::ext::IdentExt::unraw(ident).to_string().chars().enumerate() {
+        if ch.is_uppercase() {
+            if idx > 0 {
+                snake_case_name.push('_');
+            }
+            snake_case_name.extend(ch.to_lowercase());
+        } else {
+            snake_case_name.push(ch);
+        }
+    }
+    snake_case_name
+}
+
+// Every variant gets its own builder, generated exactly like the builder of a struct with the variant's fields:
+// `Event::Created { .. }` => `EventCreatedBuilder`, handed out by `Event::created_builder()`.
+// `#[builder(...)]` on the enum applies to every variant, on a variant only to that one.
+pub(crate) fn generate(st: &syn::DeriveInput, data_enum: &syn::DataEnum) -> syn::Result<proc_macro2::TokenStream> {
+    let enum_ident = &st.ident;
+    let (_, type_generics, _) = st.generics.split_for_impl();
+
+    // every variant would end up with the same builder name or constructor
+    for key in ["name", "constructor"] {
+        if let Some(lit_str) = builder_attrs::get_builder_attr_str(&st.attrs, key)? {
+            return Err(syn::Error::new(lit_str.span(), format!("`{}` is only valid on a variant, each one gets a builder of its own", key)));
+        }
+    }
+
+    let mut token_stream = proc_macro2::TokenStream::new();
+    for variant in data_enum.variants.iter() {
+        let mut attrs = variant.attrs.clone();
+        attrs.extend(st.attrs.iter().cloned());
+        super::check_struct_only_attrs(&attrs, variant.span())?;
+
+        let variant_ident = &variant.ident;
+        let variant_st = syn::DeriveInput {
+            attrs,
+            vis: st.vis.clone(),
+            ident: syn::Ident::new(&format!("{}{}", enum_ident, variant_ident), variant_ident.span()),
+            generics: st.generics.clone(),
+            data: syn::Data::Struct(syn::DataStruct {
+                struct_token: Default::default(),
+                fields: variant.fields.clone(),
+                semi_token: None,
+            }),
+        };
+        let target = BuildTarget {
+            output: quote::quote!(#enum_ident #type_generics),
+            constructor: syn::parse_quote!(#enum_ident::#variant_ident),
+            is_fn_call: false,
+            owner: Some(enum_ident.clone()),
+            builder_fn_ident: super::get_builder_fn_ident(&variant_st.attrs, &format!("{}_builder", get_snake_case_name(variant_ident)))?,
+        };
+        token_stream.extend(super::generate_builder(&variant_st, &target)?);
+    }
+    Ok(token_stream)
+}
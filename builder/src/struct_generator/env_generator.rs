@@ -0,0 +1,106 @@
+use super::{builder_attrs, error_generator, struct_fields_generator};
+
+// APP_ + max_retries => APP_MAX_RETRIES
+fn get_field_env_var_name(prefix: &syn::LitStr, field: &syn::Field) -> String {
+    let field_name = field.ident.as_ref().map(|ident| syn::ext::IdentExt::unraw(ident).to_string()).unwrap_or_default();
+    format!("{}{}", prefix.value(), field_name.to_uppercase())
+}
+
+// #[builder(env_prefix = "APP_")]: `FooBuilder::from_env()` fills in every field whose `APP_<FIELD>` variable is
+// present, parsed with `FromStr`. `each` fields are split on `env_separator` (`,` by default), with `key=value`
+// items for maps. Nested builders are left as they are, they don't know which prefix they'd be read from, and
+// `#[builder(env_skip)]` leaves out fields that can't or shouldn't be parsed. Every unparsable variable is reported
+// at once, each with its field, in the `InvalidEnvVars` error.
+pub(crate) fn generate(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let prefix = match builder_attrs::get_builder_attr_str(&st.attrs, "env_prefix")? {
+        Some(prefix) => prefix,
+        None => return Ok(proc_macro2::TokenStream::new()),
+    };
+    if !cfg!(feature = "std") {
+        return Err(syn::Error::new(
+            prefix.span(),
+            "`env_prefix` needs the `std` feature of derive_builder, the environment is only reachable through std",
+        ));
+    }
+    let fields = struct_fields_generator::get_builder_fields(st)?;
+
+    let mut read_pieces = vec![];
+    for field in fields.iter() {
+        let ident = &field.ident;
+        let var_name = get_field_env_var_name(&prefix, field);
+        let field_name = ident.as_ref().map(|ident| syn::ext::IdentExt::unraw(ident).to_string()).unwrap_or_default();
+        let read_value = if struct_fields_generator::get_field_sub_builder_type(field)?.is_some() || builder_attrs::has_builder_attr_flag(&field.attrs, "env_skip")? {
+            continue;
+        } else if let Some(item_types) = struct_fields_generator::get_field_each_item_types(field)? {
+            let separator = builder_attrs::get_builder_attr_str(&field.attrs, "env_separator")?
+                .map(|lit_str| lit_str.value())
+                .unwrap_or_else(|| ",".to_string());
+            let parse_item = if let [key_type, value_type] = item_types[..] {
+                quote::quote!(
+                    match item.split_once('=') {
+                        ::core::option::Option::Some((key, value)) => match (<#key_type as ::core::str::FromStr>::from_str(key.trim()), <#value_type as ::core::str::FromStr>::from_str(value.trim())) {
+                            (::core::result::Result::Ok(key), ::core::result::Result::Ok(value)) => ::core::result::Result::Ok((key, value)),
+                            (::core::result::Result::Err(err), _) => ::core::result::Result::Err(err.to_string()),
+                            (_, ::core::result::Result::Err(err)) => ::core::result::Result::Err(err.to_string()),
+                        },
+                        ::core::option::Option::None => ::core::result::Result::Err(::std::format!("expected `key=value`, found `{}`", item)),
+                    }
+                )
+            } else {
+                let item_type = item_types[0];
+                quote::quote!(<#item_type as ::core::str::FromStr>::from_str(item).map_err(|err| err.to_string()))
+            };
+            quote::quote!(
+                for item in value.split(#separator).map(|item| item.trim()).filter(|item| !item.is_empty()) {
+                    match #parse_item {
+                        ::core::result::Result::Ok(item) => ::core::iter::Extend::extend(&mut __builder.#ident, ::core::iter::once(item)),
+                        ::core::result::Result::Err(err) => errors.push((#field_name, err)),
+                    }
+                }
+            )
+        } else {
+            let mut value_type = struct_fields_generator::get_field_option_inner_type(field)?.unwrap_or(&field.ty);
+            let mut set_value = quote::quote!(value);
+            // a present variable of an `Option<Option<T>>` field is `Some(Some(value))`
+            if let Some(inner_type) = struct_fields_generator::get_generic_inner_type(value_type, "Option") {
+                value_type = inner_type;
+                set_value = quote::quote!(::core::option::Option::Some(value));
+            }
+            quote::quote!(
+                match <#value_type as ::core::str::FromStr>::from_str(&value) {
+                    ::core::result::Result::Ok(value) => __builder.#ident = ::core::option::Option::Some(#set_value),
+                    ::core::result::Result::Err(err) => errors.push((#field_name, err.to_string())),
+                }
+            )
+        };
+        read_pieces.push(quote::quote!(
+            match ::std::env::var(#var_name) {
+                ::core::result::Result::Ok(value) => {
+                    #read_value
+                }
+                ::core::result::Result::Err(::std::env::VarError::NotPresent) => (),
+                ::core::result::Result::Err(err) => errors.push((#field_name, err.to_string())),
+            }
+        ));
+    }
+
+    let builder_error_ident = error_generator::get_builder_error_ident(st)?;
+    let build_error_type = error_generator::get_build_error_type(st)?;
+    let vis = struct_fields_generator::get_builder_vis(st)?;
+    let from_env_docs = struct_fields_generator::generate_doc(&format!(
+        "Creates a builder with every field set whose `{}<FIELD>` environment variable is present, failing if any of them doesn't parse.",
+        prefix.value()
+    ));
+    Ok(quote::quote!(
+        #from_env_docs
+        #vis fn from_env() -> ::core::result::Result<Self, #build_error_type> {
+            let mut __builder: Self = ::core::default::Default::default();
+            let mut errors: ::std::vec::Vec<(&'static str, ::std::string::String)> = ::std::vec::Vec::new();
+            #(#read_pieces)*
+            if !errors.is_empty() {
+                return ::core::result::Result::Err(#builder_error_ident::InvalidEnvVars(errors).into());
+            }
+            ::core::result::Result::Ok(__builder)
+        }
+    ))
+}
@@ -0,0 +1,117 @@
+use super::{builder_attrs, struct_fields_generator};
+
+// CommandBuilder => CommandBuilderError
+pub(crate) fn get_builder_error_ident(st: &syn::DeriveInput) -> syn::Result<syn::Ident> {
+    let builder_ident = struct_fields_generator::get_builder_ident(st)?;
+    Ok(syn::Ident::new(&format!("{}Error", builder_ident), builder_ident.span()))
+}
+
+// The error type `build()` returns: the generated one, or whatever `#[builder(error = path)]` names,
+// which must implement `From` the generated one.
+pub(crate) fn get_build_error_type(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    if let Some(error_path) = builder_attrs::get_builder_attr_path(&st.attrs, "error")? {
+        return Ok(quote::quote!(#error_path));
+    }
+    let builder_error_ident = get_builder_error_ident(st)?;
+    Ok(quote::quote!(#builder_error_ident))
+}
+
+pub(crate) fn generate(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let builder_error_ident = get_builder_error_ident(st)?;
+    let vis = struct_fields_generator::get_builder_vis(st)?;
+    let alloc_crate = super::get_alloc_crate();
+    // `core::error::Error` is the same trait, but only since Rust 1.81, the MSRV of `#![no_std]` use noted in Cargo.toml
+    let error_trait = if cfg!(feature = "std") {
+        quote::quote!(::std::error::Error)
+    } else {
+        quote::quote!(::core::error::Error)
+    };
+    let write_missing_fields = quote::quote!({
+        for (idx, field) in fields.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(", ")?;
+            }
+            ::core::write!(f, "{} field is missing", field)?;
+        }
+        ::core::result::Result::Ok(())
+    });
+    let builder_error_docs = struct_fields_generator::generate_doc(&format!(
+        "Why [`{}::{}`] failed.",
+        struct_fields_generator::get_builder_ident(st)?,
+        struct_fields_generator::get_build_fn_ident(st)?
+    ));
+    // only a builder with nested builders can be missing fields it has no `&'static str` name for
+    let (missing_nested_fields_variant, missing_nested_fields_arms, missing_nested_fields_paths) = if struct_fields_generator::has_sub_builder_fields(st)? {
+        (
+            quote::quote!(
+                /// Every missing field by its path, like `server.port`, once a nested builder is missing some.
+                MissingNestedFields(#alloc_crate::vec::Vec<#alloc_crate::string::String>),
+            ),
+            quote::quote!(
+                #builder_error_ident::MissingNestedFields(fields) => #write_missing_fields,
+            ),
+            quote::quote!(
+                #builder_error_ident::MissingNestedFields(paths) => ::core::result::Result::Ok(paths),
+            ),
+        )
+    } else {
+        (proc_macro2::TokenStream::new(), proc_macro2::TokenStream::new(), proc_macro2::TokenStream::new())
+    };
+    // `from_env()` tells which fields its variables failed to fill in, `APP_PORT: invalid digit found in string`
+    let (invalid_env_vars_variant, invalid_env_vars_arms) = match builder_attrs::get_builder_attr_str(&st.attrs, "env_prefix")? {
+        Some(prefix) => (
+            quote::quote!(
+                /// The fields whose environment variable didn't parse, each with the reason it didn't.
+                InvalidEnvVars(#alloc_crate::vec::Vec<(&'static str, #alloc_crate::string::String)>),
+            ),
+            quote::quote!(
+                #builder_error_ident::InvalidEnvVars(fields) => {
+                    for (idx, (field, message)) in fields.iter().enumerate() {
+                        if idx > 0 {
+                            f.write_str(", ")?;
+                        }
+                        ::core::write!(f, "{}{}: {}", #prefix, field.to_uppercase(), message)?;
+                    }
+                    ::core::result::Result::Ok(())
+                }
+            ),
+        ),
+        None => (proc_macro2::TokenStream::new(), proc_macro2::TokenStream::new()),
+    };
+    Ok(quote::quote!(
+        #builder_error_docs
+        #[derive(Debug)]
+        #vis enum #builder_error_ident {
+            /// The required fields that were never set.
+            MissingFields(#alloc_crate::vec::Vec<&'static str>),
+            #missing_nested_fields_variant
+            #invalid_env_vars_variant
+            /// The message of the validator that rejected the value.
+            Validation(#alloc_crate::string::String),
+        }
+
+        impl #builder_error_ident {
+            /// Every missing field by its path, nested ones included, or the error itself when a validator failed.
+            #vis fn into_missing_field_paths(self) -> ::core::result::Result<#alloc_crate::vec::Vec<#alloc_crate::string::String>, Self> {
+                match self {
+                    #builder_error_ident::MissingFields(fields) => ::core::result::Result::Ok(fields.into_iter().map(#alloc_crate::string::String::from).collect()),
+                    #missing_nested_fields_paths
+                    err => ::core::result::Result::Err(err),
+                }
+            }
+        }
+
+        impl ::core::fmt::Display for #builder_error_ident {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                match self {
+                    #builder_error_ident::MissingFields(fields) => #write_missing_fields,
+                    #missing_nested_fields_arms
+                    #invalid_env_vars_arms
+                    #builder_error_ident::Validation(message) => f.write_str(message),
+                }
+            }
+        }
+
+        impl #error_trait for #builder_error_ident {}
+    ))
+}
@@ -0,0 +1,126 @@
+use syn::spanned::Spanned;
+
+use super::{builder_attrs, BuildTarget};
+
+// The first elided lifetime in a param type, `&str` or `Cow<'_, str>`: the builder stores the param as a field,
+// where a lifetime can't be left out. `fn(&str)` and `Fn(&str)` bring their own, so they are not looked into.
+#[derive(Default)]
+struct ElidedLifetimeFinder {
+    span: Option<proc_macro2::Span>,
+}
+
+impl<'ast> syn::visit::Visit<'ast> for ElidedLifetimeFinder {
+    fn visit_type_reference(&mut self, type_reference: &'ast syn::TypeReference) {
+        if type_reference.lifetime.is_none() && self.span.is_none() {
+            self.span = Some(type_reference.and_token.span());
+        }
+        syn::visit::visit_type_reference(self, type_reference);
+    }
+
+    fn visit_lifetime(&mut self, lifetime: &'ast syn::Lifetime) {
+        if lifetime.ident == "_" && self.span.is_none() {
+            self.span = Some(lifetime.span());
+        }
+    }
+
+    fn visit_type_bare_fn(&mut self, _: &'ast syn::TypeBareFn) {}
+
+    fn visit_parenthesized_generic_arguments(&mut self, _: &'ast syn::ParenthesizedGenericArguments) {}
+}
+
+// `#[derive_builder::builder]` on `fn connect(host: String, port: u16) -> Conn` builds the arguments of a call
+// instead of the fields of a struct: `connect_builder().host(..).port(..).call()` => `connect(host, port)`.
+// Params are classified exactly like struct fields, `#[builder(...)]` on a param works as on a field and the
+// attribute's own args as on a struct, `#[derive_builder::builder(typestate)]`.
+pub(crate) fn generate(args: proc_macro2::TokenStream, item_fn: &syn::ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let sig = &item_fn.sig;
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(syn::Error::new(asyncness.span(), "`builder` functions can't be async, `call()` would have to be awaited"));
+    }
+    if let Some(unsafety) = &sig.unsafety {
+        return Err(syn::Error::new(unsafety.span(), "`builder` functions can't be unsafe, `call()` would have to be unsafe as well"));
+    }
+    if let Some(variadic) = &sig.variadic {
+        return Err(syn::Error::new(variadic.span(), "`builder` functions can't be variadic"));
+    }
+
+    let mut attrs: Vec<syn::Attribute> = vec![];
+    if !args.is_empty() {
+        attrs.push(syn::parse_quote!(#[builder(#args)]));
+    }
+
+    let mut fields = syn::punctuated::Punctuated::<syn::Field, syn::Token![,]>::new();
+    let mut inputs = sig.inputs.clone();
+    for input in inputs.iter_mut() {
+        let pat_type = match input {
+            syn::FnArg::Receiver(receiver) => {
+                return Err(syn::Error::new(receiver.span(), "`builder` only supports free functions, a method has nowhere to put its builder"));
+            }
+            syn::FnArg::Typed(pat_type) => pat_type,
+        };
+        let ident = match &*pat_type.pat {
+            syn::Pat::Ident(pat_ident) if pat_ident.subpat.is_none() => pat_ident.ident.clone(),
+            pat => return Err(syn::Error::new(pat.span(), "`builder` params need a plain name, which their setter is named after")),
+        };
+        if let syn::Type::ImplTrait(_) = &*pat_type.ty {
+            return Err(syn::Error::new(pat_type.ty.span(), "`impl Trait` params can't be stored in the builder, use a generic param instead"));
+        }
+        let mut elided_lifetime_finder = ElidedLifetimeFinder::default();
+        syn::visit::Visit::visit_type(&mut elided_lifetime_finder, &pat_type.ty);
+        if let Some(span) = elided_lifetime_finder.span {
+            return Err(syn::Error::new(span, "params with elided lifetimes can't be stored in the builder, name the lifetime like `&'a str`"));
+        }
+        // `#[builder(...)]` only means something to the builder, the function itself can't keep it
+        let (builder_attrs, other_attrs) = pat_type.attrs.drain(..).partition(|attr| attr.path().is_ident("builder"));
+        pat_type.attrs = other_attrs;
+        fields.push(syn::Field {
+            attrs: builder_attrs,
+            vis: syn::Visibility::Inherited,
+            mutability: syn::FieldMutability::None,
+            ident: Some(ident),
+            colon_token: Some(Default::default()),
+            ty: (*pat_type.ty).clone(),
+        });
+    }
+
+    let fn_ident = &sig.ident;
+    let mut st = syn::DeriveInput {
+        attrs,
+        vis: item_fn.vis.clone(),
+        ident: syn::Ident::new(&super::get_camel_case_name(fn_ident), fn_ident.span()),
+        generics: sig.generics.clone(),
+        data: syn::Data::Struct(syn::DataStruct {
+            struct_token: Default::default(),
+            fields: syn::Fields::Named(syn::FieldsNamed {
+                brace_token: Default::default(),
+                named: fields,
+            }),
+            semi_token: None,
+        }),
+    };
+    super::check_builder_attrs(&st)?;
+    super::check_struct_only_attrs(&st.attrs, args.span())?;
+    if builder_attrs::get_builder_attr_nested_metas(&st.attrs, "build_fn")?.is_none() {
+        st.attrs.push(syn::parse_quote!(#[builder(build_fn(name = "call"))]));
+    }
+
+    let target = BuildTarget {
+        output: match &sig.output {
+            syn::ReturnType::Default => quote::quote!(()),
+            syn::ReturnType::Type(_, r#type) => quote::quote!(#r#type),
+        },
+        constructor: fn_ident.clone().into(),
+        is_fn_call: true,
+        owner: None,
+        builder_fn_ident: super::get_builder_fn_ident(&st.attrs, &format!("{}_builder", syn::ext::IdentExt::unraw(fn_ident)))?,
+    };
+    let builder = super::generate_builder(&st, &target)?;
+
+    let mut item_fn = item_fn.clone();
+    item_fn.sig.inputs = inputs;
+    Ok(quote::quote!(
+        #item_fn
+
+        #builder
+    ))
+}
@@ -1,33 +1,254 @@
+mod builder_attrs;
+mod enum_generator;
+mod env_generator;
+mod error_generator;
+mod fn_generator;
+mod serde_generator;
 mod struct_fields_generator;
+mod typestate_generator;
+
+pub(crate) use fn_generator::generate as generate_fn;
+
+// What a builder builds: the struct itself, one variant of an enum, or the result of calling a function
+pub(crate) struct BuildTarget {
+    // `Command<T>` / `Event` / `Conn`, the type `build()` returns
+    pub(crate) output: proc_macro2::TokenStream,
+    // `Command` / `Event::Created` / `connect`, the path the built value is constructed through
+    pub(crate) constructor: syn::Path,
+    // `connect(host, port)` instead of `Command { host: host, port: port }`
+    pub(crate) is_fn_call: bool,
+    // `Command` / `Event`, the type the builder constructor is defined on, `None` puts it next to the function
+    pub(crate) owner: Option<syn::Ident>,
+    // `builder` / `created_builder` / `connect_builder`
+    pub(crate) builder_fn_ident: syn::Ident,
+}
+
+// `::std` under the default `std` feature, `::alloc` for `#![no_std]` crates, which must declare `extern crate alloc`.
+// Only `Vec`, `String` and `format!` come from it, everything else generated code refers to lives in `::core`.
+pub(crate) fn get_alloc_crate() -> proc_macro2::TokenStream {
+    if cfg!(feature = "std") {
+        quote::quote!(::std)
+    } else {
+        quote::quote!(::alloc)
+    }
+}
+
+// `Event::Created` => "Event::Created", for the generated docs to link to
+pub(crate) fn get_path_doc_name(path: &syn::Path) -> String {
+    path.segments.iter().map(|seg| seg.ident.to_string()).collect::<Vec<_>>().join("::")
+}
+
+// connect_with_retry => ConnectWithRetry, r#type => Type
+pub(crate) fn get_camel_case_name(ident: &syn::Ident) -> String {
+    syn::ext::IdentExt::unraw(ident)
+        .to_string()
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|first| first.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+        })
+        .collect()
+}
+
+// `default` takes its values from an existing struct and `to_builder` turns one back into a builder,
+// an enum variant or a function call has no such value to work with
+pub(crate) fn check_struct_only_attrs(attrs: &[syn::Attribute], span: proc_macro2::Span) -> syn::Result<()> {
+    for key in ["default", "to_builder"] {
+        if builder_attrs::has_builder_attr_flag(attrs, key)? {
+            return Err(syn::Error::new(span, format!("`{}` is only supported when deriving Builder for structs", key)));
+        }
+    }
+    Ok(())
+}
+
+// Every malformed `#[builder(...)]` on the item, its variants and their fields at once, before anything reads them
+pub(crate) fn check_builder_attrs(st: &syn::DeriveInput) -> syn::Result<()> {
+    let mut errors = builder_attrs::get_builder_attr_errors(&st.attrs, builder_attrs::STRUCT_ATTR_KEYS);
+    let mut fields = vec![];
+    match &st.data {
+        syn::Data::Struct(data_struct) => fields.extend(data_struct.fields.iter()),
+        syn::Data::Enum(data_enum) => {
+            for variant in data_enum.variants.iter() {
+                errors.extend(builder_attrs::get_builder_attr_errors(&variant.attrs, builder_attrs::STRUCT_ATTR_KEYS));
+                fields.extend(variant.fields.iter());
+            }
+        }
+        syn::Data::Union(_) => (),
+    }
+    for field in fields {
+        errors.extend(builder_attrs::get_builder_attr_errors(&field.attrs, builder_attrs::FIELD_ATTR_KEYS));
+    }
+    builder_attrs::combine_errors(errors)
+}
 
-use syn::spanned::Spanned;
 pub(crate) fn generate(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
-    let struct_name_ident = &st.ident;
-    let struct_name_literal = struct_name_ident.to_string();
-    let struct_builder_name_literal = format!("{}Builder", struct_name_literal);
-    let struct_builder_name_ident = syn::Ident::new(&struct_builder_name_literal, st.span());
+    check_builder_attrs(st)?;
+    if let syn::Data::Enum(data_enum) = &st.data {
+        return enum_generator::generate(st, data_enum);
+    }
+
+    let ident = &st.ident;
+    let (_, type_generics, _) = st.generics.split_for_impl();
+    let target = BuildTarget {
+        output: quote::quote!(#ident #type_generics),
+        constructor: st.ident.clone().into(),
+        is_fn_call: false,
+        owner: Some(st.ident.clone()),
+        builder_fn_ident: get_builder_fn_ident(&st.attrs, "builder")?,
+    };
+    generate_builder(st, &target)
+}
+
+// "the [`Event::Created`]" / "the result of [`connect`]", for the docs of `build()`
+pub(crate) fn get_built_doc_name(target: &BuildTarget) -> String {
+    if target.is_fn_call {
+        format!("the result of [`{}`]", get_path_doc_name(&target.constructor))
+    } else {
+        format!("the [`{}`]", get_path_doc_name(&target.constructor))
+    }
+}
+
+// `Command::builder` / `connect_builder`, for the generated docs to link to
+pub(crate) fn get_builder_fn_doc_name(target: &BuildTarget) -> String {
+    match &target.owner {
+        Some(owner) => format!("{}::{}", owner, target.builder_fn_ident),
+        None => target.builder_fn_ident.to_string(),
+    }
+}
+
+// the built value out of the field values bound to the fields' idents
+pub(crate) fn generate_target_value(st: &syn::DeriveInput, target: &BuildTarget) -> syn::Result<proc_macro2::TokenStream> {
+    let constructor = &target.constructor;
+    let fields = struct_fields_generator::get_fields_from_derive_input(st)?;
+    let idents = fields.iter().map(|field| &field.ident);
+    if target.is_fn_call {
+        return Ok(quote::quote!(#constructor(#(#idents),*)));
+    }
+    let members = struct_fields_generator::get_struct_members(st)?;
+    Ok(quote::quote!(
+        #constructor {
+            #(#members: #idents,)*
+        }
+    ))
+}
+
+// `Command::builder()`, or a free `connect_builder()` when there is no type to hang it on
+pub(crate) fn generate_builder_fn(st: &syn::DeriveInput, target: &BuildTarget, docs: &proc_macro2::TokenStream, builder_type: &proc_macro2::TokenStream) -> syn::Result<proc_macro2::TokenStream> {
+    let vis = struct_fields_generator::get_builder_vis(st)?;
+    let builder_fn_ident = &target.builder_fn_ident;
+    let (impl_generics, type_generics, where_clause) = st.generics.split_for_impl();
+    Ok(match &target.owner {
+        Some(owner) => quote::quote!(
+            impl #impl_generics #owner #type_generics #where_clause {
+                #docs
+                #vis fn #builder_fn_ident() -> #builder_type {
+                    ::core::default::Default::default()
+                }
+            }
+        ),
+        None => quote::quote!(
+            #docs
+            #vis fn #builder_fn_ident #impl_generics() -> #builder_type #where_clause {
+                ::core::default::Default::default()
+            }
+        ),
+    })
+}
+
+// #[builder(constructor = "new_builder")], otherwise `default_name`
+pub(crate) fn get_builder_fn_ident(attrs: &[syn::Attribute], default_name: &str) -> syn::Result<syn::Ident> {
+    match builder_attrs::get_builder_attr_str(attrs, "constructor")? {
+        Some(lit_str) => lit_str.parse(),
+        None => Ok(syn::Ident::new(default_name, proc_macro2::Span::call_site())),
+    }
+}
+
+// `st` describes the fields being built, as a struct, `target` where the built value ends up
+pub(crate) fn generate_builder(st: &syn::DeriveInput, target: &BuildTarget) -> syn::Result<proc_macro2::TokenStream> {
+    if builder_attrs::has_builder_attr_flag(&st.attrs, "typestate")? {
+        return typestate_generator::generate(st, target);
+    }
+
+    let struct_builder_name_ident = struct_fields_generator::get_builder_ident(st)?;
+
+    let generics = &st.generics;
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
 
     let struct_fields_ref = struct_fields_generator::generate(st)?;
     let struct_builder_method_fileds_ref = struct_fields_generator::generate_builder_method_fields(st)?;
     let struct_builder_setter_methods = struct_fields_generator::generate_builder_setter_methods(st)?;
-    let struct_builder_build_method = struct_fields_generator::generate_builder_build_method(st)?;
+    let struct_builder_merge_methods = struct_fields_generator::generate_builder_merge_methods(st)?;
+    let struct_builder_build_method = struct_fields_generator::generate_builder_build_method(st, target)?;
+    let struct_builder_error = error_generator::generate(st)?;
+    let serde_skipped_field_attrs = serde_generator::generate_skipped_field_attrs(st)?;
+    let (struct_phantom_field, struct_phantom_init) = match struct_fields_generator::generate_builder_phantom_type(st) {
+        Some(phantom_type) => (
+            quote::quote!(#serde_skipped_field_attrs __phantom: #phantom_type,),
+            quote::quote!(__phantom: ::core::marker::PhantomData,),
+        ),
+        None => (proc_macro2::TokenStream::new(), proc_macro2::TokenStream::new()),
+    };
+    let struct_builder_from_env_method = env_generator::generate(st)?;
+    let struct_to_builder_impls =
+        struct_fields_generator::generate_to_builder_impls(st, &struct_builder_name_ident, quote::quote!(#struct_builder_name_ident #type_generics), struct_phantom_init.clone())?;
+
+    let user_derives = struct_fields_generator::get_builder_derives(st)?;
+    let mut struct_builder_derives = vec![];
+    // immutable setters clone the builder, unless asked for already the builder has to be `Clone` anyway
+    let is_clone_derived = user_derives.iter().any(|derive| derive.segments.last().map(|seg| seg.ident == "Clone").unwrap_or_default());
+    if struct_fields_generator::get_builder_pattern(st)? == struct_fields_generator::BuilderPattern::Immutable && !is_clone_derived {
+        struct_builder_derives.push(quote::quote!(::core::clone::Clone));
+    }
+    for derive in user_derives {
+        struct_builder_derives.push(quote::quote!(#derive));
+    }
+    let struct_builder_struct_attrs = struct_fields_generator::generate_builder_struct_attrs(st)?;
+    let struct_builder_serde_attrs = serde_generator::generate_struct_attrs(st)?;
+
+    let vis = struct_fields_generator::get_builder_vis(st)?;
+    let fields_summary_doc = struct_fields_generator::get_fields_summary_doc(st)?;
+    let struct_builder_docs = struct_fields_generator::generate_doc(&format!(
+        "Builder for [`{}`], handed out by [`{}`].\n\n{}",
+        get_path_doc_name(&target.constructor),
+        get_builder_fn_doc_name(target),
+        fields_summary_doc
+    ));
+    let builder_fn_docs = struct_fields_generator::generate_doc(&format!("Creates a [`{}`] with no field set yet.\n\n{}", struct_builder_name_ident, fields_summary_doc));
+    let builder_fn = generate_builder_fn(st, target, &builder_fn_docs, &quote::quote!(#struct_builder_name_ident #type_generics))?;
     Ok(quote::quote!(
-        pub struct #struct_builder_name_ident {
+        #struct_builder_docs
+        #[derive(#(#struct_builder_derives),*)]
+        #struct_builder_struct_attrs
+        #struct_builder_serde_attrs
+        #vis struct #struct_builder_name_ident #generics #where_clause {
             #struct_fields_ref
+            #struct_phantom_field
         }
 
-        impl #struct_builder_name_ident {
+        #struct_builder_error
+
+        impl #impl_generics #struct_builder_name_ident #type_generics #where_clause {
             #struct_builder_setter_methods
 
+            #struct_builder_merge_methods
+
+            #struct_builder_from_env_method
+
             #struct_builder_build_method
         }
 
-        impl #struct_name_ident {
-            pub fn builder() -> #struct_builder_name_ident {
+        impl #impl_generics ::core::default::Default for #struct_builder_name_ident #type_generics #where_clause {
+            fn default() -> Self {
                 #struct_builder_name_ident {
                     #struct_builder_method_fileds_ref
+                    #struct_phantom_init
                 }
             }
         }
+
+        #builder_fn
+
+        #struct_to_builder_impls
     ))
 }
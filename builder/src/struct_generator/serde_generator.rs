@@ -0,0 +1,59 @@
+use super::struct_fields_generator;
+
+// `#[serde(...)]` keys carried over from the struct onto the builder, so both read the same documents
+const FORWARDED_STRUCT_SERDE_KEYS: &[&str] = &["rename_all"];
+const FORWARDED_FIELD_SERDE_KEYS: &[&str] = &["rename", "alias"];
+
+// `Deserialize`, `serde::Deserialize`, ...
+pub(crate) fn is_serde_derive(path: &syn::Path) -> bool {
+    path.segments.last().map(|seg| seg.ident == "Serialize" || seg.ident == "Deserialize").unwrap_or_default()
+}
+
+fn has_serde_derive(st: &syn::DeriveInput) -> syn::Result<bool> {
+    Ok(struct_fields_generator::get_builder_derives(st)?.iter().any(is_serde_derive))
+}
+
+// #[serde(rename = "x", skip_serializing_if = "..")] with `forwarded_keys` = ["rename"] => #[serde(rename = "x")]
+fn get_forwarded_serde_attrs(attrs: &[syn::Attribute], forwarded_keys: &[&str]) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    let mut forwarded_attrs = vec![];
+    for attr in attrs {
+        if !attr.path().is_ident("serde") {
+            continue;
+        }
+        let metas = attr.parse_args_with(syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated)?;
+        for meta in metas {
+            if forwarded_keys.iter().any(|key| meta.path().is_ident(key)) {
+                forwarded_attrs.push(quote::quote!(#[serde(#meta)]));
+            }
+        }
+    }
+    Ok(forwarded_attrs)
+}
+
+pub(crate) fn generate_struct_attrs(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    if !has_serde_derive(st)? {
+        return Ok(proc_macro2::TokenStream::new());
+    }
+    // every field is optional in the document, whatever is missing is left as `FooBuilder::default()` has it
+    let forwarded_attrs = get_forwarded_serde_attrs(&st.attrs, FORWARDED_STRUCT_SERDE_KEYS)?;
+    Ok(quote::quote!(
+        #[serde(default)]
+        #(#forwarded_attrs)*
+    ))
+}
+
+pub(crate) fn generate_field_attrs(st: &syn::DeriveInput, field: &syn::Field) -> syn::Result<proc_macro2::TokenStream> {
+    if !has_serde_derive(st)? {
+        return Ok(proc_macro2::TokenStream::new());
+    }
+    let field_attrs = get_forwarded_serde_attrs(&field.attrs, FORWARDED_FIELD_SERDE_KEYS)?;
+    Ok(quote::quote!(#(#field_attrs)*))
+}
+
+// fields the builder holds on top of the struct's own, which never appear in a document
+pub(crate) fn generate_skipped_field_attrs(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    if !has_serde_derive(st)? {
+        return Ok(proc_macro2::TokenStream::new());
+    }
+    Ok(quote::quote!(#[serde(skip)]))
+}
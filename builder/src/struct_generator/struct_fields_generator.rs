@@ -1,24 +1,114 @@
 use std::vec;
 
-use syn::{parse::Parser, spanned::Spanned};
+use syn::spanned::Spanned;
+
+use super::{builder_attrs, error_generator, serde_generator, BuildTarget};
 
 type StructFields = syn::punctuated::Punctuated<syn::Field, syn::Token![,]>;
-fn get_fields_from_derive_input(st: &syn::DeriveInput) -> syn::Result<&StructFields> {
-    if let syn::Data::Struct(syn::DataStruct {
-        fields: syn::Fields::Named(syn::FieldsNamed { named, .. }),
-        ..
-    }) = &st.data
-    {
-        Ok(named)
-    } else {
-        Err(syn::Error::new_spanned(st, "Must Define on Struct, Not on Enum"))
+// Tuple struct fields get an ident too, `#[builder(name = "...")]` or `field0`, `field1`, ..., which names
+// both their builder field and their setter. Unit structs have no fields at all.
+pub(crate) fn get_fields_from_derive_input(st: &syn::DeriveInput) -> syn::Result<StructFields> {
+    match &st.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(syn::FieldsNamed { named, .. }),
+            ..
+        }) => {
+            for field in named.iter() {
+                if let Some(lit_str) = builder_attrs::get_builder_attr_str(&field.attrs, "name")? {
+                    return Err(syn::Error::new(lit_str.span(), "`name` is only for tuple struct fields, named fields already have one"));
+                }
+            }
+            Ok(named.clone())
+        }
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Unnamed(syn::FieldsUnnamed { unnamed, .. }),
+            ..
+        }) => {
+            let mut fields = unnamed.clone();
+            for (idx, field) in fields.iter_mut().enumerate() {
+                field.ident = Some(match builder_attrs::get_builder_attr_str(&field.attrs, "name")? {
+                    Some(lit_str) => lit_str.parse()?,
+                    None => syn::Ident::new(&format!("field{}", idx), field.span()),
+                });
+            }
+            Ok(fields)
+        }
+        syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Unit, .. }) => Ok(StructFields::new()),
+        syn::Data::Enum(_) => Err(syn::Error::new_spanned(st, "Builder can only be derived for structs, not enums")),
+        syn::Data::Union(_) => Err(syn::Error::new_spanned(st, "Builder can only be derived for structs, not unions")),
     }
 }
 
-fn get_generic_inner_type<'a>(r#type: &'a syn::Type, outer_ident_name: &str) -> Option<&'a syn::Type> {
-    if let syn::Type::Path(syn::TypePath { path: syn::Path { segments, .. }, .. }) = r#type {
-        if let Some(seg) = segments.last() {
-            if seg.ident.to_string() == outer_ident_name {
+// How each field is reached on the struct itself, in the same order as `get_fields_from_derive_input`:
+// `host` for named fields, `0` for tuple struct fields
+pub(crate) fn get_struct_members(st: &syn::DeriveInput) -> syn::Result<Vec<syn::Member>> {
+    Ok(get_fields_from_derive_input(st)?
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| match &st.data {
+            syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Unnamed(_), .. }) => syn::Member::Unnamed(syn::Index {
+                index: idx as u32,
+                span: field.span(),
+            }),
+            _ => syn::Member::Named(field.ident.clone().unwrap()),
+        })
+        .collect())
+}
+
+// keys that only make sense for a field the builder has a setter for
+const SETTER_FIELD_ATTR_KEYS: &[&str] = &["each", "setter", "vis", "private", "sub_builder", "merge", "env_separator", "env_skip", "try_setter"];
+
+// #[builder(skip)] and #[builder(computed = "expr")] fields are left out of the builder, `build()` fills them in itself
+pub(crate) fn is_builder_field(field: &syn::Field) -> syn::Result<bool> {
+    let is_skip = builder_attrs::has_builder_attr_flag(&field.attrs, "skip")?;
+    let computed = builder_attrs::get_builder_attr_str(&field.attrs, "computed")?;
+    let key = match (is_skip, &computed) {
+        (false, None) => return Ok(true),
+        (true, Some(lit_str)) => return Err(syn::Error::new(lit_str.span(), "`computed` fields are already skipped, `skip` can't be combined with it")),
+        (true, None) => "skip",
+        (false, Some(_)) => "computed",
+    };
+    for other_key in SETTER_FIELD_ATTR_KEYS.iter().chain(if key == "computed" { &["default"][..] } else { &[] }) {
+        if builder_attrs::has_builder_attr_flag(&field.attrs, other_key)? || builder_attrs::get_builder_attr_str(&field.attrs, other_key)?.is_some() {
+            return Err(syn::Error::new(
+                field.span(),
+                format!("`{}` fields aren't set through the builder, `{}` doesn't apply to them", key, other_key),
+            ));
+        }
+    }
+    Ok(false)
+}
+
+// The fields the builder holds and has setters for, in declaration order
+pub(crate) fn get_builder_fields(st: &syn::DeriveInput) -> syn::Result<Vec<syn::Field>> {
+    let mut builder_fields = vec![];
+    for field in get_fields_from_derive_input(st)? {
+        if is_builder_field(&field)? {
+            builder_fields.push(field);
+        }
+    }
+    Ok(builder_fields)
+}
+
+fn get_field_member(st: &syn::DeriveInput, field: &syn::Field) -> syn::Result<syn::Member> {
+    let fields = get_fields_from_derive_input(st)?;
+    let idx = fields.iter().position(|other| other.ident == field.ident).unwrap_or_default();
+    Ok(get_struct_members(st)?.swap_remove(idx))
+}
+
+// `Option<T>`, `std::option::Option<T>` or `core::option::Option<T>` with `outer_ident_name` = "Option" => T,
+// leading `::` included.
+// Any other path ending in the same name, like `my::Option<T>`, is a different type and gives `None`.
+pub(crate) fn get_generic_inner_type<'a>(r#type: &'a syn::Type, outer_ident_name: &str) -> Option<&'a syn::Type> {
+    if let syn::Type::Path(syn::TypePath { qself: None, path }) = r#type {
+        let module_name = outer_ident_name.to_lowercase();
+        let is_known_path = match &path.segments.iter().map(|seg| seg.ident.to_string()).collect::<Vec<_>>()[..] {
+            [_] => path.leading_colon.is_none(),
+            [krate, module, _] => ["std", "core", "alloc"].contains(&krate.as_str()) && *module == module_name,
+            _ => false,
+        };
+        if let Some(seg) = path.segments.last() {
+            if is_known_path && seg.ident == outer_ident_name {
                 if let syn::PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments { args, .. }) = &seg.arguments {
                     if let Some(syn::GenericArgument::Type(inner_type)) = args.first() {
                         return Some(inner_type);
@@ -30,87 +120,194 @@ fn get_generic_inner_type<'a>(r#type: &'a syn::Type, outer_ident_name: &str) ->
     None
 }
 
-fn get_field_macro_attr_path_value(field: &syn::Field, attr_path: &str, allowed_outer_ident_names: Option<Vec<&str>>) -> syn::Result<Option<syn::Ident>> {
-    for attr in &field.attrs {
-        if let syn::Meta::List(list) = &attr.meta {
-            if let Some(p) = list.path.segments.first() {
-                if p.ident == "builder" {
-                    let nested_metas = syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated.parse2(list.tokens.clone()).unwrap();
-                    for nested_meta in nested_metas.iter() {
-                        if let syn::Meta::NameValue(kv) = nested_meta {
-                            if kv.path.is_ident(attr_path) {
-                                match &kv.value {
-                                    syn::Expr::Lit(expr) => {
-                                        if let syn::Lit::Str(ref ident_str) = expr.lit {
-                                            return Ok(Some(syn::Ident::new(ident_str.value().as_str(), field.span())));
-                                        }
-                                    }
-                                    _ => (),
-                                }
-                            }
-                            if let Some(ref allowed_outer_ident_names) = allowed_outer_ident_names {
-                                match kv.path.get_ident() {
-                                    Some(kv_path_ident) => {
-                                        let kv_path_name = kv_path_ident.to_string();
-                                        if allowed_outer_ident_names.iter().find(|allowed_name| *allowed_name == &kv_path_name).is_none() {
-                                            return Err(syn::Error::new_spanned(&list, format!(r#"expected `builder({} = "...")`"#, allowed_outer_ident_names.join("|"))));
-                                        }
-                                    }
-                                    _ => (),
-                                }
-                            }
-                        }
-                    }
+// The `T` of an optional `Option<T>` field. `#[builder(required)]` makes the field an ordinary one,
+// whose setter takes the whole `Option<T>` and which `build()` insists on being set.
+pub(crate) fn get_field_option_inner_type(field: &syn::Field) -> syn::Result<Option<&syn::Type>> {
+    if builder_attrs::has_builder_attr_flag(&field.attrs, "required")? {
+        if builder_attrs::has_builder_attr_flag(&field.attrs, "optional")? {
+            return Err(syn::Error::new(field.span(), "a field can't be both `optional` and `required`"));
+        }
+        return Ok(None);
+    }
+    Ok(get_generic_inner_type(&field.ty, "Option"))
+}
+
+// Vec<T> / HashSet<T> / VecDeque<T> => [T], HashMap<K, V> / BTreeMap<K, V> => [K, V]
+// Only the name tells a map apart: a type named `...Map` with two type args is extended with `(K, V)`, anything else,
+// `Bitmap<u8>` included, with its first type arg. A map named otherwise can go through an alias like `type FooMap<K, V>`.
+fn get_collection_item_types(r#type: &syn::Type) -> Option<Vec<&syn::Type>> {
+    if let syn::Type::Path(syn::TypePath { path: syn::Path { segments, .. }, .. }) = r#type {
+        if let Some(seg) = segments.last() {
+            if let syn::PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments { args, .. }) = &seg.arguments {
+                let type_args: Vec<_> = args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        syn::GenericArgument::Type(type_arg) => Some(type_arg),
+                        _ => None,
+                    })
+                    .collect();
+                if seg.ident.to_string().ends_with("Map") && type_args.len() >= 2 {
+                    return Some(type_args[..2].to_vec());
+                }
+                if let Some(item_type) = type_args.first() {
+                    return Some(vec![*item_type]);
                 }
             }
         }
     }
+    None
+}
+
+// The item types an `each` field is extended with one at a time, `None` if the field has no `each`
+pub(crate) fn get_field_each_item_types(field: &syn::Field) -> syn::Result<Option<Vec<&syn::Type>>> {
+    if get_field_macro_attr_path_value(field, "each")?.is_none() {
+        return Ok(None);
+    }
+    match get_collection_item_types(&field.ty) {
+        Some(item_types) => Ok(Some(item_types)),
+        None => Err(syn::Error::new(
+            field.span(),
+            "`each` field must be a `Default + Extend` collection like Vec<T>, HashSet<T> or HashMap<K, V>, \
+             where a type named `...Map` with two type args is taken as a map of `(K, V)` and any other by its first type arg",
+        )),
+    }
+}
+
+// #[builder(sub_builder)] on a `path::Bar<T>` field => `path::Bar<T>` with its last segment renamed, `suffix` = "Builder"
+// gives the builder the field is stored as, `suffix` = "BuilderError" (without the generic args) the error its `build()` fails with
+// The field's type is all there is to go by, so the nested builder must be derived as it is by default: named `BarBuilder`,
+// failing with `BarBuilderError`, built by `build()` with the mutable pattern, and not `typestate`. Builders like that
+// carry `NESTABLE_MARKER_NAME`, which the outer `build()` asks for first, so that any other one is reported at the field.
+const NESTABLE_MARKER_NAME: &str = "__NESTABLE_AS_SUB_BUILDER_WITH_DEFAULT_NAME_BUILD_FN_ERROR_AND_PATTERN";
+
+fn get_field_sub_builder_path(field: &syn::Field, suffix: &str) -> syn::Result<Option<syn::Path>> {
+    if !builder_attrs::has_builder_attr_flag(&field.attrs, "sub_builder")? {
+        return Ok(None);
+    }
+    match &field.ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => {
+            let mut path = path.clone();
+            let seg = path.segments.last_mut().unwrap();
+            seg.ident = syn::Ident::new(&format!("{}{}", seg.ident, suffix), seg.ident.span());
+            if suffix != "Builder" {
+                seg.arguments = syn::PathArguments::None;
+            }
+            Ok(Some(path))
+        }
+        _ => Err(syn::Error::new_spanned(&field.ty, "`sub_builder` field must be of a struct type that derives Builder")),
+    }
+}
+
+pub(crate) fn get_field_sub_builder_type(field: &syn::Field) -> syn::Result<Option<syn::Path>> {
+    get_field_sub_builder_path(field, "Builder")
+}
+
+pub(crate) fn get_field_macro_attr_path_value(field: &syn::Field, attr_path: &str) -> syn::Result<Option<syn::Ident>> {
+    Ok(builder_attrs::get_builder_attr_str(&field.attrs, attr_path)?.map(|lit_str| syn::Ident::new(lit_str.value().as_str(), field.span())))
+}
+
+// #[builder(default)] => Default::default(), #[builder(default = "expr")] => expr
+pub(crate) fn get_field_macro_attr_default(field: &syn::Field) -> syn::Result<Option<proc_macro2::TokenStream>> {
+    if let Some(lit_str) = builder_attrs::get_builder_attr_str(&field.attrs, "default")? {
+        let expr: syn::Expr = lit_str.parse()?;
+        return Ok(Some(quote::quote!(#expr)));
+    }
+    if builder_attrs::has_builder_attr_flag(&field.attrs, "default")? {
+        return Ok(Some(quote::quote!(::core::default::Default::default())));
+    }
+    Ok(None)
+}
+
+// What `build()` falls back to when the field was never set: its own `default`, the struct's `Default`
+// impl under a struct-level `#[builder(default)]`, or `None` for an `optional` field whose type hides the `Option`.
+fn get_field_fallback_value(st: &syn::DeriveInput, field: &syn::Field) -> syn::Result<Option<proc_macro2::TokenStream>> {
+    if let Some(default) = get_field_macro_attr_default(field)? {
+        if builder_attrs::has_builder_attr_flag(&field.attrs, "required")? {
+            return Err(syn::Error::new(field.span(), "`required` fields have to be set, a `default` would never be used"));
+        }
+        return Ok(Some(default));
+    }
+    if builder_attrs::has_builder_attr_flag(&st.attrs, "default")? {
+        let member = get_field_member(st, field)?;
+        return Ok(Some(quote::quote!(__default.#member)));
+    }
+    // `type Maybe<T> = Option<T>`, whose setter takes the whole `Maybe<T>` since there is no telling what `T` is
+    if builder_attrs::has_builder_attr_flag(&field.attrs, "optional")? && get_field_option_inner_type(field)?.is_none() {
+        return Ok(Some(quote::quote!(::core::default::Default::default())));
+    }
     Ok(None)
 }
 
+// Neither `Option<T>`, `each` nor defaulted fields: `build()` can't succeed until they are set.
+pub(crate) fn is_required_field(st: &syn::DeriveInput, field: &syn::Field) -> syn::Result<bool> {
+    if get_field_sub_builder_type(field)?.is_some() {
+        // whatever the nested builder is missing gets reported by its own `build()`
+        return Ok(false);
+    }
+    Ok(get_field_option_inner_type(field)?.is_none() && get_field_macro_attr_path_value(field, "each")?.is_none() && get_field_fallback_value(st, field)?.is_none())
+}
+
 pub(crate) fn generate(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
-    let fields = get_fields_from_derive_input(st)?;
+    let fields = get_builder_fields(st)?;
 
     let idents: Vec<_> = fields.iter().map(|field| &field.ident).collect();
+    let mut attrs = vec![];
     let mut types = vec![];
     for field in fields.iter() {
+        attrs.push(serde_generator::generate_field_attrs(st, field)?);
         let r#type = &field.ty;
-        if let Some(inner_type) = get_generic_inner_type(r#type, "Option") {
-            types.push(quote::quote!(std::option::Option<#inner_type>))
-        } else if let Some(_) = get_field_macro_attr_path_value(field, "each", Some(vec!["each"]))? {
-            if let Some(_) = get_generic_inner_type(r#type, "Vec") {
-                types.push(quote::quote!(#r#type))
-            } else {
-                return Err(syn::Error::new(field.span(), "`each` field must be a Vec type"));
-            }
+        if let Some(sub_builder_type) = get_field_sub_builder_type(field)? {
+            types.push(quote::quote!(#sub_builder_type))
+        } else if let Some(inner_type) = get_field_option_inner_type(field)? {
+            types.push(quote::quote!(::core::option::Option<#inner_type>))
+        } else if get_field_each_item_types(field)?.is_some() {
+            types.push(quote::quote!(#r#type))
         } else {
-            types.push(quote::quote!(std::option::Option<#r#type>))
+            types.push(quote::quote!(::core::option::Option<#r#type>))
         }
     }
 
     Ok(quote::quote!(
-        #(#idents: #types),*
+        #(#attrs #idents: #types,)*
     ))
 }
 
+// `PhantomData<fn() -> (&'a (), T)>`, holding on to every lifetime and type param of the builder. The builder of
+// an enum variant carries all of the enum's generics, whether or not that variant's fields use them.
+pub(crate) fn generate_builder_phantom_type(st: &syn::DeriveInput) -> Option<proc_macro2::TokenStream> {
+    let mut phantom_types = vec![];
+    for param in st.generics.params.iter() {
+        match param {
+            syn::GenericParam::Lifetime(lifetime_param) => {
+                let lifetime = &lifetime_param.lifetime;
+                phantom_types.push(quote::quote!(&#lifetime ()));
+            }
+            syn::GenericParam::Type(type_param) => {
+                let ident = &type_param.ident;
+                phantom_types.push(quote::quote!(#ident));
+            }
+            syn::GenericParam::Const(_) => (),
+        }
+    }
+    if phantom_types.is_empty() {
+        return None;
+    }
+    Some(quote::quote!(::core::marker::PhantomData<fn() -> (#(#phantom_types,)*)>))
+}
+
 pub(crate) fn generate_builder_method_fields(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
-    let fields = get_fields_from_derive_input(st)?;
+    let fields = get_builder_fields(st)?;
 
     let mut builder_clauses = vec![];
     for field in fields.iter() {
         let ident = &field.ident;
-        let r#type = &field.ty;
-        if let Some(_) = get_field_macro_attr_path_value(field, "each", Some(vec!["each"]))? {
-            if let Some(_) = get_generic_inner_type(r#type, "Vec") {
-                builder_clauses.push(quote::quote!(
-                    #ident: std::vec::Vec::new(),
-                ))
-            } else {
-                return Err(syn::Error::new(field.span(), "`each` field must be a Vec type"));
-            }
+        if get_field_sub_builder_type(field)?.is_some() || get_field_each_item_types(field)?.is_some() {
+            builder_clauses.push(quote::quote!(
+                #ident: ::core::default::Default::default(),
+            ))
         } else {
             builder_clauses.push(quote::quote!(
-                #ident: std::option::Option::None,
+                #ident: ::core::option::Option::None,
             ))
         }
     }
@@ -120,50 +317,448 @@ pub(crate) fn generate_builder_method_fields(st: &syn::DeriveInput) -> syn::Resu
     ))
 }
 
-pub(crate) fn generate_builder_setter_methods(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
-    let fields = get_fields_from_derive_input(st)?;
+// #[builder(pattern = "owned" | "mutable" | "immutable")], how setters and `build()` take the builder
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum BuilderPattern {
+    // setters take `self` and return it, `build(self)` moves the fields out
+    Owned,
+    // setters take `&mut self` and return it, `build(&mut self)` clones the fields
+    Mutable,
+    // setters take `&self` and return a modified clone, `build(&self)` clones the fields
+    Immutable,
+}
 
-    let mut build_setter_methods = vec![];
-    for field in fields.iter() {
-        let ident = &field.ident;
-        let r#type = &field.ty;
+// #[builder(name = "CommandOptions")] on the struct, otherwise `CommandBuilder`
+pub(crate) fn get_builder_ident(st: &syn::DeriveInput) -> syn::Result<syn::Ident> {
+    match builder_attrs::get_builder_attr_str(&st.attrs, "name")? {
+        Some(lit_str) => lit_str.parse(),
+        None => Ok(syn::Ident::new(&format!("{}Builder", st.ident), st.ident.span())),
+    }
+}
+
+// #[builder(build_fn(name = "finish"))] on the struct, otherwise `build`
+pub(crate) fn get_build_fn_ident(st: &syn::DeriveInput) -> syn::Result<syn::Ident> {
+    let build_fn_metas = builder_attrs::get_builder_attr_nested_metas(&st.attrs, "build_fn")?.unwrap_or_default();
+    match builder_attrs::get_nested_meta_str(&build_fn_metas, "name") {
+        Some(lit_str) => lit_str.parse(),
+        None => Ok(syn::Ident::new("build", st.ident.span())),
+    }
+}
+
+// #[builder(setter(name = "..."))] on the field, otherwise the field's name behind the struct's
+// #[builder(setter_prefix = "with_")]. Only the plain setter is renamed, `clear_<field>` and friends keep the field's name.
+pub(crate) fn get_setter_ident(st: &syn::DeriveInput, field: &syn::Field) -> syn::Result<syn::Ident> {
+    let setter_metas = builder_attrs::get_builder_attr_nested_metas(&field.attrs, "setter")?.unwrap_or_default();
+    if let Some(lit_str) = builder_attrs::get_nested_meta_str(&setter_metas, "name") {
+        return lit_str.parse();
+    }
+    let ident = field.ident.as_ref().unwrap();
+    match builder_attrs::get_builder_attr_str(&st.attrs, "setter_prefix")? {
+        // already checked to make identifiers, an empty prefix leaves the field's name as it is, `r#` included
+        Some(lit_str) if !lit_str.value().is_empty() => Ok(syn::Ident::new(&format!("{}{}", lit_str.value(), syn::ext::IdentExt::unraw(ident)), ident.span())),
+        _ => Ok(ident.clone()),
+    }
+}
+
+// #[builder(derive(Debug, Clone))] => [Debug, Clone], the derives put on the builder struct
+pub(crate) fn get_builder_derives(st: &syn::DeriveInput) -> syn::Result<Vec<syn::Path>> {
+    let mut derives = vec![];
+    for meta in builder_attrs::get_builder_attr_all_nested_metas(&st.attrs, "derive")? {
+        let path = match meta {
+            syn::Meta::Path(path) => path,
+            _ => return Err(syn::Error::new_spanned(meta, "expected a trait name like `Debug`")),
+        };
+        if path.segments.last().map(|seg| seg.ident == "Default").unwrap_or_default() {
+            return Err(syn::Error::new_spanned(path, "the builder always implements `Default`, there is no need to derive it"));
+        }
+        if serde_generator::is_serde_derive(&path) && !cfg!(feature = "serde") {
+            return Err(syn::Error::new_spanned(
+                path,
+                "deriving `Serialize` or `Deserialize` onto the builder requires the `serde` feature of derive_builder",
+            ));
+        }
+        derives.push(path);
+    }
+    Ok(derives)
+}
+
+// #[builder(struct_attr(allow(dead_code)))] => #[allow(dead_code)], put on the builder struct as is
+pub(crate) fn generate_builder_struct_attrs(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let struct_attrs = builder_attrs::get_builder_attr_all_nested_metas(&st.attrs, "struct_attr")?;
+    Ok(quote::quote!(#(#[#struct_attrs])*))
+}
 
-        if let Some(inner_type) = get_generic_inner_type(r#type, "Option") {
-            build_setter_methods.push(quote::quote!(
-                fn #ident(&mut self, #ident: #inner_type) -> &mut Self {
-                    self.#ident = std::option::Option::Some(#ident);
+// #[builder(vis = "pub(crate)")] on the struct, otherwise the struct's own visibility
+pub(crate) fn get_builder_vis(st: &syn::DeriveInput) -> syn::Result<syn::Visibility> {
+    match builder_attrs::get_builder_attr_str(&st.attrs, "vis")? {
+        Some(lit_str) => lit_str.parse(),
+        None => Ok(st.vis.clone()),
+    }
+}
+
+// #[builder(private)] or #[builder(vis = "pub(crate)")] on the field, otherwise the builder's visibility
+pub(crate) fn get_setter_vis(st: &syn::DeriveInput, field: &syn::Field) -> syn::Result<syn::Visibility> {
+    if builder_attrs::has_builder_attr_flag(&field.attrs, "private")? {
+        return Ok(syn::Visibility::Inherited);
+    }
+    match builder_attrs::get_builder_attr_str(&field.attrs, "vis")? {
+        Some(lit_str) => lit_str.parse(),
+        None => get_builder_vis(st),
+    }
+}
+
+pub(crate) fn get_builder_pattern(st: &syn::DeriveInput) -> syn::Result<BuilderPattern> {
+    match builder_attrs::get_builder_attr_str(&st.attrs, "pattern")? {
+        None => Ok(BuilderPattern::Mutable),
+        Some(lit_str) => match lit_str.value().as_str() {
+            "owned" => Ok(BuilderPattern::Owned),
+            "mutable" => Ok(BuilderPattern::Mutable),
+            "immutable" => Ok(BuilderPattern::Immutable),
+            _ => Err(syn::Error::new(lit_str.span(), r#"expected `pattern = "owned" | "mutable" | "immutable"`"#)),
+        },
+    }
+}
+
+// #[doc = "..."] for a generated item
+pub(crate) fn generate_doc(text: &str) -> proc_macro2::TokenStream {
+    quote::quote!(#[doc = #text])
+}
+
+// The field's own `///` docs for the setters standing in for it, `fallback` when it has none
+pub(crate) fn generate_field_docs(field: &syn::Field, fallback: &str) -> proc_macro2::TokenStream {
+    let docs: Vec<_> = field.attrs.iter().filter(|attr| attr.path().is_ident("doc")).collect();
+    if docs.is_empty() {
+        return generate_doc(fallback);
+    }
+    quote::quote!(#(#docs)*)
+}
+
+// "Required fields: `host`, `port`. Optional fields: `timeout`.", repeated on the builder, its constructor and `build()`
+pub(crate) fn get_fields_summary_doc(st: &syn::DeriveInput) -> syn::Result<String> {
+    let mut required_fields = vec![];
+    let mut optional_fields = vec![];
+    for field in get_builder_fields(st)?.iter() {
+        let field_name = field.ident.as_ref().map(|ident| format!("`{}`", syn::ext::IdentExt::unraw(ident))).unwrap_or_default();
+        if is_required_field(st, field)? {
+            required_fields.push(field_name);
+        } else {
+            optional_fields.push(field_name);
+        }
+    }
+    let mut summary = vec![];
+    if !required_fields.is_empty() {
+        summary.push(format!("Required fields: {}.", required_fields.join(", ")));
+    }
+    if !optional_fields.is_empty() {
+        summary.push(format!("Optional fields: {}.", optional_fields.join(", ")));
+    }
+    if summary.is_empty() {
+        summary.push("There are no fields to set.".to_string());
+    }
+    Ok(summary.join(" "))
+}
+
+// `assign` writes the new value into whatever builder expression it is given
+fn generate_setter_method(
+    vis: &syn::Visibility,
+    docs: proc_macro2::TokenStream,
+    pattern: BuilderPattern,
+    setter_ident: &syn::Ident,
+    setter_generics: Vec<proc_macro2::TokenStream>,
+    params: proc_macro2::TokenStream,
+    assign: impl Fn(proc_macro2::TokenStream) -> proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    match pattern {
+        BuilderPattern::Owned => {
+            let assign = assign(quote::quote!(self));
+            quote::quote!(
+                #docs
+                #vis fn #setter_ident<#(#setter_generics),*>(mut self, #params) -> Self {
+                    #assign
                     self
                 }
-            ))
-        } else if let Some(ref user_ident) = get_field_macro_attr_path_value(field, "each", Some(vec!["each"]))? {
-            if let Some(inner_type) = get_generic_inner_type(r#type, "Vec") {
-                let mut token_stream = proc_macro2::TokenStream::new();
-                token_stream.extend(quote::quote!(
-                    fn #user_ident(&mut self, #user_ident: #inner_type) -> &mut Self {
-                        self.#ident.push(#user_ident);
-                        self
-                    }
-                ));
-                if Some(user_ident) != ident.as_ref() {
-                    token_stream.extend(quote::quote!(
-                        fn #ident(&mut self, #ident: #r#type) -> &mut Self {
-                            self.#ident = #ident;
-                            self
-                        }
-                    ));
+            )
+        }
+        BuilderPattern::Mutable => {
+            let assign = assign(quote::quote!(self));
+            quote::quote!(
+                #docs
+                #vis fn #setter_ident<#(#setter_generics),*>(&mut self, #params) -> &mut Self {
+                    #assign
+                    self
                 }
-                build_setter_methods.push(token_stream)
-            } else {
-                return Err(syn::Error::new(field.span(), "`each` field must be a Vec type"));
+            )
+        }
+        BuilderPattern::Immutable => {
+            let assign = assign(quote::quote!(__builder));
+            quote::quote!(
+                #docs
+                #vis fn #setter_ident<#(#setter_generics),*>(&self, #params) -> Self {
+                    let mut __builder = ::core::clone::Clone::clone(self);
+                    #assign
+                    __builder
+                }
+            )
+        }
+    }
+}
+
+// A setter's value param: `(generic params, param type, value expr)`, which is `([], T, value)` normally and
+// `([__V: Into<T>], __V, value.into())` under `#[builder(setter(into))]`
+pub(crate) fn get_setter_value_param(
+    field: &syn::Field,
+    param_ident: &syn::Ident,
+    value_type: &syn::Type,
+    generic_name: &str,
+) -> syn::Result<(Vec<proc_macro2::TokenStream>, proc_macro2::TokenStream, proc_macro2::TokenStream)> {
+    let is_into = match builder_attrs::get_builder_attr_nested_metas(&field.attrs, "setter")? {
+        Some(setter_metas) => builder_attrs::has_nested_meta_flag(&setter_metas, "into"),
+        None => false,
+    };
+    if is_into {
+        let generic_ident = syn::Ident::new(generic_name, param_ident.span());
+        Ok((
+            vec![quote::quote!(#generic_ident: ::core::convert::Into<#value_type>)],
+            quote::quote!(#generic_ident),
+            quote::quote!(#param_ident.into()),
+        ))
+    } else {
+        Ok((vec![], quote::quote!(#value_type), quote::quote!(#param_ident)))
+    }
+}
+
+pub(crate) fn generate_builder_field_setter_methods(st: &syn::DeriveInput, field: &syn::Field, vis: &syn::Visibility, pattern: BuilderPattern) -> syn::Result<proc_macro2::TokenStream> {
+    let ident = &field.ident;
+    let setter_ident = &get_setter_ident(st, field)?;
+    let field_name = ident.as_ref().map(|ident| syn::ext::IdentExt::unraw(ident).to_string()).unwrap_or_default();
+    let r#type = &field.ty;
+
+    let mut token_stream = proc_macro2::TokenStream::new();
+    if let Some(sub_builder_type) = get_field_sub_builder_type(field)? {
+        // `.server(|server| server.host(..).port(..))`, or `.server_mut()` to reach into the nested builder directly
+        token_stream.extend(generate_setter_method(
+            vis,
+            generate_field_docs(field, &format!("Configures `{}` through its own builder.", field_name)),
+            pattern,
+            setter_ident,
+            vec![],
+            quote::quote!(f: impl ::core::ops::FnOnce(&mut #sub_builder_type) -> &mut #sub_builder_type),
+            |builder| quote::quote!(f(&mut #builder.#ident);),
+        ));
+        let mut_setter_ident = syn::Ident::new(&format!("{}_mut", field_name), setter_ident.span());
+        let mut_setter_docs = generate_doc(&format!("Gives direct access to the nested builder of `{}`.", field_name));
+        token_stream.extend(quote::quote!(
+            #mut_setter_docs
+            #vis fn #mut_setter_ident(&mut self) -> &mut #sub_builder_type {
+                &mut self.#ident
             }
+        ));
+    } else if let Some(inner_type) = get_field_option_inner_type(field)? {
+        let (setter_generics, param_type, value) = get_setter_value_param(field, ident.as_ref().unwrap(), inner_type, "__V")?;
+        // `Option<Option<T>>` keeps apart never set (`None`) and explicitly set to nothing (`Some(None)`)
+        let setter_doc = if get_generic_inner_type(inner_type, "Option").is_some() {
+            format!("Sets `{}`, `None` included: left unset it builds as `None`, set to `None` as `Some(None)`.", field_name)
         } else {
-            build_setter_methods.push(quote::quote!(
-                fn #ident(&mut self, #ident: #r#type) -> &mut Self {
-                    self.#ident = std::option::Option::Some(#ident);
-                    self
+            format!("Sets `{}`.", field_name)
+        };
+        token_stream.extend(generate_setter_method(
+            vis,
+            generate_field_docs(field, &setter_doc),
+            pattern,
+            setter_ident,
+            setter_generics,
+            quote::quote!(#ident: #param_type),
+            |builder| quote::quote!(#builder.#ident = ::core::option::Option::Some(#value);),
+        ));
+        // the setter above can only ever set `Some`, these two let callers put the field back to `None`
+        let clear_setter_ident = syn::Ident::new(&format!("clear_{}", field_name), setter_ident.span());
+        token_stream.extend(generate_setter_method(
+            vis,
+            generate_doc(&format!("Resets `{}` to `None`.", field_name)),
+            pattern,
+            &clear_setter_ident,
+            vec![],
+            proc_macro2::TokenStream::new(),
+            |builder| quote::quote!(#builder.#ident = ::core::option::Option::None;),
+        ));
+        let opt_setter_ident = syn::Ident::new(&format!("{}_opt", field_name), setter_ident.span());
+        token_stream.extend(generate_setter_method(
+            vis,
+            generate_doc(&format!("Sets `{}` to `Some` or `None` as given.", field_name)),
+            pattern,
+            &opt_setter_ident,
+            vec![],
+            quote::quote!(#ident: #r#type),
+            |builder| quote::quote!(#builder.#ident = #ident;),
+        ));
+    } else if let Some(item_types) = get_field_each_item_types(field)? {
+        let user_ident = get_field_macro_attr_path_value(field, "each")?.unwrap();
+        let each_setter_docs = generate_field_docs(field, &format!("Adds one item to `{}`.", field_name));
+        if let [key_type, value_type] = item_types[..] {
+            // maps get `fn env(key, value)` rather than `fn env((key, value))`
+            let (mut setter_generics, key_param_type, key) = get_setter_value_param(field, &syn::Ident::new("key", user_ident.span()), key_type, "__K")?;
+            let (value_generics, value_param_type, value) = get_setter_value_param(field, &syn::Ident::new("value", user_ident.span()), value_type, "__V")?;
+            setter_generics.extend(value_generics);
+            token_stream.extend(generate_setter_method(
+                vis,
+                each_setter_docs.clone(),
+                pattern,
+                &user_ident,
+                setter_generics,
+                quote::quote!(key: #key_param_type, value: #value_param_type),
+                |builder| quote::quote!(::core::iter::Extend::extend(&mut #builder.#ident, ::core::iter::once((#key, #value)));),
+            ));
+        } else {
+            let (setter_generics, param_type, value) = get_setter_value_param(field, &user_ident, item_types[0], "__V")?;
+            token_stream.extend(generate_setter_method(
+                vis,
+                each_setter_docs,
+                pattern,
+                &user_ident,
+                setter_generics,
+                quote::quote!(#user_ident: #param_type),
+                |builder| quote::quote!(::core::iter::Extend::extend(&mut #builder.#ident, ::core::iter::once(#value));),
+            ));
+        }
+        if user_ident != *setter_ident {
+            token_stream.extend(generate_setter_method(
+                vis,
+                generate_doc(&format!("Replaces all of `{}` at once.", field_name)),
+                pattern,
+                setter_ident,
+                vec![],
+                quote::quote!(#ident: #r#type),
+                |builder| quote::quote!(#builder.#ident = #ident;),
+            ));
+        }
+        let extend_setter_ident = syn::Ident::new(&format!("extend_{}", field_name), setter_ident.span());
+        token_stream.extend(generate_setter_method(
+            vis,
+            generate_doc(&format!("Adds every item of `items` to `{}`.", field_name)),
+            pattern,
+            &extend_setter_ident,
+            vec![quote::quote!(__I: ::core::iter::IntoIterator<Item = (#(#item_types),*)>)],
+            quote::quote!(items: __I),
+            |builder| quote::quote!(::core::iter::Extend::extend(&mut #builder.#ident, items);),
+        ));
+    } else {
+        let (setter_generics, param_type, value) = get_setter_value_param(field, ident.as_ref().unwrap(), r#type, "__V")?;
+        token_stream.extend(generate_setter_method(
+            vis,
+            generate_field_docs(field, &format!("Sets `{}`.", field_name)),
+            pattern,
+            setter_ident,
+            setter_generics,
+            quote::quote!(#ident: #param_type),
+            |builder| quote::quote!(#builder.#ident = ::core::option::Option::Some(#value);),
+        ));
+    }
+    Ok(token_stream)
+}
+
+// #[builder(try_setter)]: `try_port<V: TryInto<u16>>(port)`, which hands the converted value to the plain setter
+// and gives back the conversion error instead. `receiver` and `output` are the plain setter's.
+pub(crate) fn generate_builder_field_try_setter_method(
+    st: &syn::DeriveInput,
+    field: &syn::Field,
+    vis: &syn::Visibility,
+    receiver: proc_macro2::TokenStream,
+    output: proc_macro2::TokenStream,
+) -> syn::Result<proc_macro2::TokenStream> {
+    if !builder_attrs::has_builder_attr_flag(&field.attrs, "try_setter")? {
+        return Ok(proc_macro2::TokenStream::new());
+    }
+    if get_field_sub_builder_type(field)?.is_some() || get_field_each_item_types(field)?.is_some() {
+        return Err(syn::Error::new(field.span(), "`try_setter` only applies to fields that are set as a whole"));
+    }
+    let ident = &field.ident;
+    let setter_ident = get_setter_ident(st, field)?;
+    let try_setter_ident = syn::Ident::new(&format!("try_{}", syn::ext::IdentExt::unraw(&setter_ident)), setter_ident.span());
+    let value_type = get_field_option_inner_type(field)?.unwrap_or(&field.ty);
+    let field_name = ident.as_ref().map(|ident| syn::ext::IdentExt::unraw(ident).to_string()).unwrap_or_default();
+    let try_setter_docs = generate_doc(&format!("Converts the value with `TryInto` and sets `{}`, or gives back the conversion error.", field_name));
+    Ok(quote::quote!(
+        #try_setter_docs
+        #vis fn #try_setter_ident<__V: ::core::convert::TryInto<#value_type>>(#receiver, #ident: __V) -> ::core::result::Result<#output, <__V as ::core::convert::TryInto<#value_type>>::Error> {
+            ::core::result::Result::Ok(self.#setter_ident(::core::convert::TryInto::try_into(#ident)?))
+        }
+    ))
+}
+
+// #[builder(to_builder)]: `From<Foo> for FooBuilder` and `Foo::to_builder(&self)`, which pre-populate every field.
+// `builder_type` is the builder as seen from the struct's own generics, `extra_fields` initializes anything
+// the builder holds on top of the struct's fields.
+pub(crate) fn generate_to_builder_impls(
+    st: &syn::DeriveInput,
+    builder_ident: &syn::Ident,
+    builder_type: proc_macro2::TokenStream,
+    extra_fields: proc_macro2::TokenStream,
+) -> syn::Result<proc_macro2::TokenStream> {
+    if !builder_attrs::has_builder_attr_flag(&st.attrs, "to_builder")? {
+        return Ok(proc_macro2::TokenStream::new());
+    }
+    let fields = get_fields_from_derive_input(st)?;
+
+    let members = get_struct_members(st)?;
+    let mut moved_fields = vec![];
+    let mut cloned_fields = vec![];
+    for (field, member) in fields.iter().zip(members.iter()) {
+        if !is_builder_field(field)? {
+            continue;
+        }
+        let ident = &field.ident;
+        if get_field_sub_builder_type(field)?.is_some() {
+            moved_fields.push(quote::quote!(#ident: ::core::convert::From::from(value.#member),));
+            cloned_fields.push(quote::quote!(#ident: ::core::convert::From::from(::core::clone::Clone::clone(&self.#member)),));
+        } else if get_field_option_inner_type(field)?.is_some() || get_field_each_item_types(field)?.is_some() {
+            moved_fields.push(quote::quote!(#ident: value.#member,));
+            cloned_fields.push(quote::quote!(#ident: ::core::clone::Clone::clone(&self.#member),));
+        } else {
+            moved_fields.push(quote::quote!(#ident: ::core::option::Option::Some(value.#member),));
+            cloned_fields.push(quote::quote!(#ident: ::core::option::Option::Some(::core::clone::Clone::clone(&self.#member)),));
+        }
+    }
+
+    let struct_ident = &st.ident;
+    let vis = get_builder_vis(st)?;
+    let (impl_generics, type_generics, where_clause) = st.generics.split_for_impl();
+    let to_builder_docs = generate_doc(&format!("Creates a [`{}`] with every field set from this value.", builder_ident));
+    Ok(quote::quote!(
+        impl #impl_generics ::core::convert::From<#struct_ident #type_generics> for #builder_type #where_clause {
+            fn from(value: #struct_ident #type_generics) -> Self {
+                Self {
+                    #(#moved_fields)*
+                    #extra_fields
                 }
-            ))
+            }
+        }
+
+        impl #impl_generics #struct_ident #type_generics #where_clause {
+            #to_builder_docs
+            #vis fn to_builder(&self) -> #builder_type {
+                #builder_ident {
+                    #(#cloned_fields)*
+                    #extra_fields
+                }
+            }
         }
+    ))
+}
+
+pub(crate) fn generate_builder_setter_methods(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = get_builder_fields(st)?;
+    let pattern = get_builder_pattern(st)?;
+
+    let mut build_setter_methods = vec![];
+    let (receiver, output) = match pattern {
+        BuilderPattern::Owned => (quote::quote!(self), quote::quote!(Self)),
+        BuilderPattern::Mutable => (quote::quote!(&mut self), quote::quote!(&mut Self)),
+        BuilderPattern::Immutable => (quote::quote!(&self), quote::quote!(Self)),
+    };
+    for field in fields.iter() {
+        let vis = get_setter_vis(st, field)?;
+        build_setter_methods.push(generate_builder_field_setter_methods(st, field, &vis, pattern)?);
+        build_setter_methods.push(generate_builder_field_try_setter_method(st, field, &vis, receiver.clone(), output.clone())?);
     }
 
     Ok(quote::quote!(
@@ -171,47 +766,382 @@ pub(crate) fn generate_builder_setter_methods(st: &syn::DeriveInput) -> syn::Res
     ))
 }
 
-pub(crate) fn generate_builder_build_method(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
-    let fields = get_fields_from_derive_input(st)?;
+// #[builder(merge = "append" | "replace")] on an `each` field, whether `merge()` extends the collection or swaps it out
+fn is_merge_replace_field(field: &syn::Field) -> syn::Result<bool> {
+    match builder_attrs::get_builder_attr_str(&field.attrs, "merge")? {
+        None => Ok(false),
+        Some(lit_str) if get_field_each_item_types(field)?.is_none() => Err(syn::Error::new(lit_str.span(), "`merge` only applies to `each` fields, every other field is replaced when set")),
+        Some(lit_str) => match lit_str.value().as_str() {
+            "append" => Ok(false),
+            "replace" => Ok(true),
+            _ => Err(syn::Error::new(lit_str.span(), r#"expected `merge = "append" | "replace"`"#)),
+        },
+    }
+}
 
-    let mut build_validate_pieces = vec![];
+// `merge` and `is_<field>_set` sit next to the setters, so a field called `merge` or `is_port_set` needs its setter renamed
+fn check_merge_method_names(st: &syn::DeriveInput, fields: &[syn::Field]) -> syn::Result<()> {
+    let mut reserved_names = vec!["merge".to_string()];
+    for field in fields.iter() {
+        if get_field_sub_builder_type(field)?.is_none() {
+            let field_name = field.ident.as_ref().map(|ident| syn::ext::IdentExt::unraw(ident).to_string()).unwrap_or_default();
+            reserved_names.push(format!("is_{}_set", field_name));
+        }
+    }
+    for field in fields.iter() {
+        let mut setter_idents = vec![(get_setter_ident(st, field)?, "setter(name = \"...\")")];
+        if let Some(each_ident) = get_field_macro_attr_path_value(field, "each")? {
+            setter_idents.push((each_ident, "each = \"...\""));
+        }
+        for (setter_ident, rename_usage) in setter_idents {
+            let setter_name = syn::ext::IdentExt::unraw(&setter_ident).to_string();
+            if reserved_names.contains(&setter_name) {
+                return Err(syn::Error::new(
+                    setter_ident.span(),
+                    format!("the setter `{}` collides with the generated `{}()`, rename it with `{}`", setter_name, setter_name, rename_usage),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+// `merge(other)` lays `other` over the builder: whatever is set in `other` wins, `each` collections are appended to
+// unless marked `merge = "replace"`, and nested builders are merged in turn. `is_<field>_set()` tells what is set so far.
+pub(crate) fn generate_builder_merge_methods(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    enum MergeKind {
+        Nested(syn::Path),
+        Append,
+        Replace,
+        Overwrite,
+    }
+
+    let fields = get_builder_fields(st)?;
+    let pattern = get_builder_pattern(st)?;
+    check_merge_method_names(st, &fields)?;
+
+    let mut is_set_methods = vec![];
+    let mut merge_kinds = vec![];
     for field in fields.iter() {
         let ident = &field.ident;
-        let r#type = &field.ty;
-        if get_generic_inner_type(r#type, "Option").is_none() && get_field_macro_attr_path_value(field, "each", Some(vec!["each"]))?.is_none() {
-            build_validate_pieces.push(quote::quote!(
-                if self.#ident.is_none() {
-                    let err = format!("{} field is missing", stringify!(#ident));
-                    return std::result::Result::Err(err.into());
+        let field_name = ident.as_ref().map(|ident| syn::ext::IdentExt::unraw(ident).to_string()).unwrap_or_default();
+        let is_set_ident = syn::Ident::new(&format!("is_{}_set", field_name), field.span());
+        let vis = get_setter_vis(st, field)?;
+        if let Some(sub_builder_type) = get_field_sub_builder_type(field)? {
+            // a nested builder is never set as a whole, its own `is_<field>_set()` are reachable through `<field>_mut()`
+            merge_kinds.push((ident, MergeKind::Nested(sub_builder_type)));
+        } else if get_field_each_item_types(field)?.is_some() {
+            let is_set_docs = generate_doc(&format!("Whether any item has been added to `{}`.", field_name));
+            is_set_methods.push(quote::quote!(
+                #is_set_docs
+                #vis fn #is_set_ident(&self) -> bool {
+                    ::core::iter::IntoIterator::into_iter(&self.#ident).next().is_some()
                 }
-            ))
+            ));
+            merge_kinds.push((ident, if is_merge_replace_field(field)? { MergeKind::Replace } else { MergeKind::Append }));
+        } else {
+            let is_set_docs = generate_doc(&format!("Whether `{}` has been set.", field_name));
+            is_set_methods.push(quote::quote!(
+                #is_set_docs
+                #vis fn #is_set_ident(&self) -> bool {
+                    self.#ident.is_some()
+                }
+            ));
+            merge_kinds.push((ident, MergeKind::Overwrite));
         }
     }
 
-    let mut build_assign_pieces = vec![];
+    let vis = get_builder_vis(st)?;
+    let merge_ident = syn::Ident::new("merge", st.span());
+    let merge_docs = generate_doc("Lays `other` over this builder: fields set in `other` win, collections are appended to or replaced, nested builders are merged in turn.");
+    let merge_method = generate_setter_method(&vis, merge_docs, pattern, &merge_ident, vec![], quote::quote!(other: Self), |builder| {
+        let merge_pieces = merge_kinds.iter().map(|(ident, merge_kind)| match merge_kind {
+            // only a mutable nested builder merges in place, any other pattern would drop the merged builder on the floor
+            MergeKind::Nested(sub_builder_type) => quote::quote_spanned!(sub_builder_type.span()=>
+                let _: &mut #sub_builder_type = <#sub_builder_type>::merge(&mut #builder.#ident, other.#ident);
+            ),
+            MergeKind::Append => quote::quote!(
+                ::core::iter::Extend::extend(&mut #builder.#ident, other.#ident);
+            ),
+            MergeKind::Replace => quote::quote!(
+                let mut items = ::core::iter::IntoIterator::into_iter(other.#ident).peekable();
+                if items.peek().is_some() {
+                    #builder.#ident = ::core::default::Default::default();
+                    ::core::iter::Extend::extend(&mut #builder.#ident, items);
+                }
+            ),
+            MergeKind::Overwrite => quote::quote!(
+                if other.#ident.is_some() {
+                    #builder.#ident = other.#ident;
+                }
+            ),
+        });
+        quote::quote!(#(#merge_pieces)*)
+    });
+    Ok(quote::quote!(
+        #merge_method
+        #(#is_set_methods)*
+    ))
+}
+
+// Resolves every field into a local of the same name, in declaration order, so a `default = "expr"`
+// can refer to the fields declared before it. `computed` fields come last and can refer to every other field.
+// Required fields must have been checked for presence already.
+pub(crate) fn generate_builder_build_field_values(st: &syn::DeriveInput, clone_fields: bool) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = get_fields_from_derive_input(st)?;
+
+    let mut build_value_pieces = vec![];
+    if builder_attrs::has_builder_attr_flag(&st.attrs, "default")? {
+        let struct_ident = &st.ident;
+        let (_, type_generics, _) = st.generics.split_for_impl();
+        build_value_pieces.push(quote::quote!(
+            let __default: #struct_ident #type_generics = ::core::default::Default::default();
+        ));
+    }
+    let mut computed_value_pieces = vec![];
     for field in fields.iter() {
         let ident = &field.ident;
+        let value = if clone_fields { quote::quote!(self.#ident.clone()) } else { quote::quote!(self.#ident) };
         let r#type = &field.ty;
-        if get_generic_inner_type(r#type, "Option").is_some() || get_field_macro_attr_path_value(field, "each", Some(vec!["each"]))?.is_some() {
-            build_assign_pieces.push(quote::quote!(
-                #ident: self.#ident.clone(),
+        if let Some(lit_str) = builder_attrs::get_builder_attr_str(&field.attrs, "computed")? {
+            let expr: syn::Expr = lit_str.parse()?;
+            computed_value_pieces.push(quote::quote!(
+                let #ident: #r#type = #expr;
+            ));
+        } else if builder_attrs::has_builder_attr_flag(&field.attrs, "skip")? {
+            let fallback = get_field_fallback_value(st, field)?.unwrap_or_else(|| quote::quote!(::core::default::Default::default()));
+            build_value_pieces.push(quote::quote!(
+                let #ident: #r#type = #fallback;
+            ));
+        } else if get_field_sub_builder_type(field)?.is_some() {
+            // built up front by `generate_builder_build_method`, which collects its missing fields
+            build_value_pieces.push(quote::quote!(
+                let #ident = #ident.unwrap();
+            ));
+        } else if get_field_macro_attr_path_value(field, "each")?.is_some() {
+            build_value_pieces.push(quote::quote!(
+                let #ident = #value;
+            ));
+        } else if let Some(fallback) = get_field_fallback_value(st, field)? {
+            let set_value = if get_field_option_inner_type(field)?.is_some() {
+                quote::quote!(::core::option::Option::Some(value))
+            } else {
+                quote::quote!(value)
+            };
+            build_value_pieces.push(quote::quote!(
+                let #ident = match #value {
+                    ::core::option::Option::Some(value) => #set_value,
+                    ::core::option::Option::None => #fallback,
+                };
+            ));
+        } else if get_field_option_inner_type(field)?.is_some() {
+            build_value_pieces.push(quote::quote!(
+                let #ident = #value;
             ));
         } else {
-            build_assign_pieces.push(quote::quote!(
-                #ident: self.#ident.clone().unwrap(),
+            build_value_pieces.push(quote::quote!(
+                let #ident = #value.unwrap();
             ));
         }
     }
 
-    let struct_ident = &st.ident;
     Ok(quote::quote!(
-        pub fn build(&mut self) -> std::result::Result<#struct_ident, std::boxed::Box<dyn std::error::Error>> {
-            #(#build_validate_pieces)*
+        #(#build_value_pieces)*
+        #(#computed_value_pieces)*
+    ))
+}
 
-            let ret = #struct_ident {
-                #(#build_assign_pieces)*
+pub(crate) fn has_sub_builder_fields(st: &syn::DeriveInput) -> syn::Result<bool> {
+    for field in get_builder_fields(st)?.iter() {
+        if get_field_sub_builder_type(field)?.is_some() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+pub(crate) fn has_builder_validation(st: &syn::DeriveInput) -> syn::Result<bool> {
+    if builder_attrs::get_builder_attr_path(&st.attrs, "validate")?.is_some() {
+        return Ok(true);
+    }
+    for field in get_fields_from_derive_input(st)?.iter() {
+        if builder_attrs::get_builder_attr_path(&field.attrs, "validate")?.is_some() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+// #[builder(validate = path)] on the struct: `path(&builder)` runs once every required field is known to be set
+pub(crate) fn generate_builder_validate_builder(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let builder_error_ident = error_generator::get_builder_error_ident(st)?;
+    match builder_attrs::get_builder_attr_path(&st.attrs, "validate")? {
+        Some(validate_path) => Ok(quote::quote!(
+            if let ::core::result::Result::Err(message) = #validate_path(&self) {
+                return ::core::result::Result::Err(#builder_error_ident::Validation(message).into());
+            }
+        )),
+        None => Ok(proc_macro2::TokenStream::new()),
+    }
+}
+
+// #[builder(validate = path)] on a field: `path(&value)` runs on the resolved value of that field,
+// defaults included, right before the struct is constructed
+pub(crate) fn generate_builder_validate_field_values(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = get_fields_from_derive_input(st)?;
+    let builder_error_ident = error_generator::get_builder_error_ident(st)?;
+    let alloc_crate = super::get_alloc_crate();
+
+    let mut validate_pieces = vec![];
+    for field in fields.iter() {
+        if let Some(validate_path) = builder_attrs::get_builder_attr_path(&field.attrs, "validate")? {
+            let ident = &field.ident;
+            let field_name = ident.as_ref().map(|ident| syn::ext::IdentExt::unraw(ident).to_string());
+            validate_pieces.push(quote::quote!(
+                if let ::core::result::Result::Err(message) = #validate_path(&#ident) {
+                    return ::core::result::Result::Err(#builder_error_ident::Validation(#alloc_crate::format!("{}: {}", #field_name, message)).into());
+                }
+            ));
+        }
+    }
+
+    Ok(quote::quote!(
+        #(#validate_pieces)*
+    ))
+}
+
+pub(crate) fn generate_builder_build_method(st: &syn::DeriveInput, target: &BuildTarget) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = get_builder_fields(st)?;
+    let pattern = get_builder_pattern(st)?;
+    let builder_error_ident = error_generator::get_builder_error_ident(st)?;
+    let alloc_crate = super::get_alloc_crate();
+    let has_sub_builders = has_sub_builder_fields(st)?;
+    let nestable_marker_ident = syn::Ident::new(NESTABLE_MARKER_NAME, st.ident.span());
+
+    let mut build_validate_pieces = vec![];
+    for field in fields.iter() {
+        let ident = &field.ident;
+        let field_name = ident.as_ref().map(|ident| syn::ext::IdentExt::unraw(ident).to_string());
+        if let (Some(sub_builder_type), Some(sub_builder_error_path)) = (get_field_sub_builder_type(field)?, get_field_sub_builder_path(field, "BuilderError")?) {
+            // nested builders use the default mutable pattern, so their `build()` takes `&mut self`; built in place
+            // rather than moved out, a struct-level validator still gets to see the whole builder afterwards.
+            // An immutable outer builder only has `&self`, so it needs a `#[derive(Clone)]` nested builder to build from
+            let sub_builder_marker_ident = syn::Ident::new(NESTABLE_MARKER_NAME, field.ty.span());
+            let sub_build = match pattern {
+                BuilderPattern::Owned | BuilderPattern::Mutable => quote::quote_spanned!(field.ty.span()=> {
+                    let () = <#sub_builder_type>::#sub_builder_marker_ident;
+                    <#sub_builder_type>::build(&mut self.#ident)
+                }),
+                BuilderPattern::Immutable => quote::quote_spanned!(field.ty.span()=> {
+                    let () = <#sub_builder_type>::#sub_builder_marker_ident;
+                    <#sub_builder_type as ::core::clone::Clone>::clone(&self.#ident).build()
+                }),
             };
-            std::result::Result::Ok(ret)
+            // `server.port field is missing` rather than `port field is missing`
+            build_validate_pieces.push(quote::quote!(
+                let #ident = match #sub_build {
+                    ::core::result::Result::Ok(value) => ::core::option::Option::Some(value),
+                    ::core::result::Result::Err(err) => match #sub_builder_error_path::into_missing_field_paths(err) {
+                        ::core::result::Result::Ok(paths) => {
+                            missing_field_paths.extend(paths.into_iter().map(|path| #alloc_crate::format!("{}.{}", #field_name, path)));
+                            ::core::option::Option::None
+                        }
+                        ::core::result::Result::Err(err) => {
+                            return ::core::result::Result::Err(#builder_error_ident::Validation(#alloc_crate::format!("{}.{}", #field_name, err)).into());
+                        }
+                    },
+                };
+            ))
+        } else if is_required_field(st, field)? {
+            let push_field_path = if has_sub_builders {
+                quote::quote!(missing_field_paths.push(#alloc_crate::string::String::from(#field_name));)
+            } else {
+                proc_macro2::TokenStream::new()
+            };
+            build_validate_pieces.push(quote::quote!(
+                if self.#ident.is_none() {
+                    missing_fields.push(#field_name);
+                    #push_field_path
+                }
+            ))
+        }
+    }
+    // own missing fields are listed in `MissingFields` as asked for, once a nested builder reports some too
+    // every one of them is listed by its path in `MissingNestedFields`, in declaration order
+    let (declare_missing_field_paths, check_missing_field_paths) = if has_sub_builders {
+        (
+            quote::quote!(
+                let mut missing_field_paths: #alloc_crate::vec::Vec<#alloc_crate::string::String> = #alloc_crate::vec::Vec::new();
+            ),
+            quote::quote!(
+                if missing_field_paths.len() > missing_fields.len() {
+                    return ::core::result::Result::Err(#builder_error_ident::MissingNestedFields(missing_field_paths).into());
+                }
+            ),
+        )
+    } else {
+        (proc_macro2::TokenStream::new(), proc_macro2::TokenStream::new())
+    };
+
+    let build_field_values = generate_builder_build_field_values(st, pattern != BuilderPattern::Owned)?;
+    let validate_builder = generate_builder_validate_builder(st)?;
+    let validate_field_values = generate_builder_validate_field_values(st)?;
+    let receiver = match pattern {
+        BuilderPattern::Owned if has_sub_builders => quote::quote!(mut self),
+        BuilderPattern::Owned => quote::quote!(self),
+        BuilderPattern::Mutable => quote::quote!(&mut self),
+        BuilderPattern::Immutable => quote::quote!(&self),
+    };
+    let output = &target.output;
+    let target_value = super::generate_target_value(st, target)?;
+    let build_error_type = error_generator::get_build_error_type(st)?;
+    let vis = get_builder_vis(st)?;
+    let build_fn_ident = get_build_fn_ident(st)?;
+    let missing_nested_fields_doc = if has_sub_builders {
+        format!(" ([`{}::MissingNestedFields`] when a nested builder misses some too)", builder_error_ident)
+    } else {
+        String::new()
+    };
+    let build_fn_docs = generate_doc(&format!(
+        "Builds {}, failing with [`{}::MissingFields`]{} unless every required field is set, or with [`{}::Validation`] when a validator rejects it.\n\n{}",
+        super::get_built_doc_name(target),
+        builder_error_ident,
+        missing_nested_fields_doc,
+        builder_error_ident,
+        get_fields_summary_doc(st)?
+    ));
+    // only a builder `sub_builder` knows how to drive gets the marker, the others fail on it at the outer field's type
+    let nestable_marker = if pattern == BuilderPattern::Mutable
+        && build_fn_ident == "build"
+        && builder_attrs::get_builder_attr_path(&st.attrs, "error")?.is_none()
+        && builder_attrs::get_builder_attr_str(&st.attrs, "name")?.is_none()
+    {
+        quote::quote!(
+            #[doc(hidden)]
+            #vis const #nestable_marker_ident: () = ();
+        )
+    } else {
+        proc_macro2::TokenStream::new()
+    };
+    Ok(quote::quote!(
+        #nestable_marker
+
+        #build_fn_docs
+        #vis fn #build_fn_ident(#receiver) -> ::core::result::Result<#output, #build_error_type> {
+            let mut missing_fields: #alloc_crate::vec::Vec<&'static str> = #alloc_crate::vec::Vec::new();
+            #declare_missing_field_paths
+            #(#build_validate_pieces)*
+            #check_missing_field_paths
+            if !missing_fields.is_empty() {
+                return ::core::result::Result::Err(#builder_error_ident::MissingFields(missing_fields).into());
+            }
+            #validate_builder
+
+
+            #build_field_values
+            #validate_field_values
+            let ret = #target_value;
+            ::core::result::Result::Ok(ret)
         }
     ))
 }
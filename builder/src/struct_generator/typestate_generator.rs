@@ -0,0 +1,248 @@
+use syn::spanned::Spanned;
+
+use super::{builder_attrs, error_generator, serde_generator, struct_fields_generator, BuildTarget};
+
+// the first required field => __S0, numbered rather than named after the field, which `x` and `_x` would share
+fn get_field_state_param_ident(field: &syn::Field, state_index: usize) -> syn::Ident {
+    syn::Ident::new(&format!("__S{}", state_index), field.span())
+}
+
+// <'a, T: Clone, const N: usize> => ['a, T, N]
+fn get_generic_args(generics: &syn::Generics) -> Vec<proc_macro2::TokenStream> {
+    generics
+        .params
+        .iter()
+        .map(|param| match param {
+            syn::GenericParam::Lifetime(lifetime_param) => {
+                let lifetime = &lifetime_param.lifetime;
+                quote::quote!(#lifetime)
+            }
+            syn::GenericParam::Type(type_param) => {
+                let ident = &type_param.ident;
+                quote::quote!(#ident)
+            }
+            syn::GenericParam::Const(const_param) => {
+                let ident = &const_param.ident;
+                quote::quote!(#ident)
+            }
+        })
+        .collect()
+}
+
+// the struct's own generics followed by the given state params, which always come last
+fn extend_generics<'a>(generics: &syn::Generics, state_params: impl IntoIterator<Item = &'a syn::Ident>) -> syn::Generics {
+    let mut generics = generics.clone();
+    for state_param in state_params {
+        generics.params.push(syn::parse_quote!(#state_param));
+    }
+    generics
+}
+
+// Every required field adds one state param to the builder, which is either the `Set` or the `Unset` marker.
+// Setters of required fields turn their own param into `Set` whatever it was before, and `build()` is only implemented
+// once every param is `Set`, so forgetting a required field is a compile error instead of a runtime one.
+pub(crate) fn generate(st: &syn::DeriveInput, target: &BuildTarget) -> syn::Result<proc_macro2::TokenStream> {
+    if let Some(lit_str) = builder_attrs::get_builder_attr_str(&st.attrs, "pattern")? {
+        return Err(syn::Error::new(
+            lit_str.span(),
+            "`typestate` builders always take the builder by value, `pattern` can't be combined with it",
+        ));
+    }
+    if let Some(lit_str) = builder_attrs::get_builder_attr_str(&st.attrs, "env_prefix")? {
+        return Err(syn::Error::new(
+            lit_str.span(),
+            "`typestate` builders can't be read from the environment, which fields get set is only known at runtime",
+        ));
+    }
+    let builder_derives = struct_fields_generator::get_builder_derives(st)?;
+    if let Some(derive) = builder_derives.iter().find(|derive| serde_generator::is_serde_derive(derive)) {
+        return Err(syn::Error::new_spanned(derive, "`typestate` builders can't be deserialized, their state is part of their type"));
+    }
+
+    let output = &target.output;
+    let struct_builder_name_ident = struct_fields_generator::get_builder_ident(st)?;
+    let build_fn_ident = struct_fields_generator::get_build_fn_ident(st)?;
+    let set_marker_ident = syn::Ident::new(&format!("{}Set", struct_builder_name_ident), st.span());
+    let unset_marker_ident = syn::Ident::new(&format!("{}Unset", struct_builder_name_ident), st.span());
+
+    let vis = struct_fields_generator::get_builder_vis(st)?;
+    let generic_args = get_generic_args(&st.generics);
+    let (struct_impl_generics, _, struct_where_clause) = st.generics.split_for_impl();
+
+    let fields = struct_fields_generator::get_builder_fields(st)?;
+    let idents: Vec<_> = fields.iter().map(|field| &field.ident).collect();
+    let target_value = super::generate_target_value(st, target)?;
+
+    let mut state_params = vec![];
+    for field in fields.iter() {
+        if builder_attrs::has_builder_attr_flag(&field.attrs, "sub_builder")? {
+            return Err(syn::Error::new(
+                field.span(),
+                "`sub_builder` fields can only be reported missing at runtime, which `typestate` builders don't do",
+            ));
+        }
+        if struct_fields_generator::is_required_field(st, field)? {
+            state_params.push(get_field_state_param_ident(field, state_params.len()));
+        }
+    }
+
+    let mut setter_methods = vec![];
+    let mut state_index = 0;
+    for field in fields.iter() {
+        let ident = &field.ident;
+        let r#type = &field.ty;
+
+        if struct_fields_generator::is_required_field(st, field)? {
+            let output_states: Vec<_> = state_params
+                .iter()
+                .enumerate()
+                .map(|(idx, param)| if idx == state_index { quote::quote!(#set_marker_ident) } else { quote::quote!(#param) })
+                .collect();
+            let other_idents = idents.iter().filter(|other| **other != ident);
+            let setter_vis = struct_fields_generator::get_setter_vis(st, field)?;
+            let setter_ident = struct_fields_generator::get_setter_ident(st, field)?;
+            let (setter_generics, param_type, value) = struct_fields_generator::get_setter_value_param(field, ident.as_ref().unwrap(), r#type, "__V")?;
+            let field_name = ident.as_ref().map(|ident| syn::ext::IdentExt::unraw(ident).to_string()).unwrap_or_default();
+            let setter_docs = struct_fields_generator::generate_field_docs(field, &format!("Sets `{}`.", field_name));
+            setter_methods.push(quote::quote!(
+                #setter_docs
+                #setter_vis fn #setter_ident<#(#setter_generics),*>(self, #ident: #param_type) -> #struct_builder_name_ident<#(#generic_args,)* #(#output_states),*> {
+                    #struct_builder_name_ident {
+                        #ident: ::core::option::Option::Some(#value),
+                        #(#other_idents: self.#other_idents,)*
+                        __state: ::core::marker::PhantomData,
+                    }
+                }
+            ));
+            setter_methods.push(struct_fields_generator::generate_builder_field_try_setter_method(
+                st,
+                field,
+                &setter_vis,
+                quote::quote!(self),
+                quote::quote!(#struct_builder_name_ident<#(#generic_args,)* #(#output_states),*>),
+            )?);
+            state_index += 1;
+        } else {
+            let setter_vis = struct_fields_generator::get_setter_vis(st, field)?;
+            setter_methods.push(struct_fields_generator::generate_builder_field_setter_methods(
+                st,
+                field,
+                &setter_vis,
+                struct_fields_generator::BuilderPattern::Owned,
+            )?);
+            setter_methods.push(struct_fields_generator::generate_builder_field_try_setter_method(
+                st,
+                field,
+                &setter_vis,
+                quote::quote!(self),
+                quote::quote!(Self),
+            )?);
+        }
+    }
+
+    let build_field_values = struct_fields_generator::generate_builder_build_field_values(st, false)?;
+    let fields_summary_doc = struct_fields_generator::get_fields_summary_doc(st)?;
+    let build_fn_docs = struct_fields_generator::generate_doc(&format!(
+        "Builds {}, which is only possible once every required field is set.\n\n{}",
+        super::get_built_doc_name(target),
+        fields_summary_doc
+    ));
+    // with nothing left to fail `build()` hands back the struct itself, validation brings the error type back
+    let mut builder_error = proc_macro2::TokenStream::new();
+    let build_method = if struct_fields_generator::has_builder_validation(st)? {
+        let validate_builder = struct_fields_generator::generate_builder_validate_builder(st)?;
+        let validate_field_values = struct_fields_generator::generate_builder_validate_field_values(st)?;
+        let build_error_type = error_generator::get_build_error_type(st)?;
+        builder_error = error_generator::generate(st)?;
+        quote::quote!(
+            #build_fn_docs
+            #vis fn #build_fn_ident(self) -> ::core::result::Result<#output, #build_error_type> {
+                #validate_builder
+                #build_field_values
+                #validate_field_values
+                ::core::result::Result::Ok(#target_value)
+            }
+        )
+    } else {
+        quote::quote!(
+            #build_fn_docs
+            #vis fn #build_fn_ident(self) -> #output {
+                #build_field_values
+                #target_value
+            }
+        )
+    };
+
+    let struct_fields_ref = struct_fields_generator::generate(st)?;
+    let struct_builder_method_fileds_ref = struct_fields_generator::generate_builder_method_fields(st)?;
+    let set_states = state_params.iter().map(|_| &set_marker_ident);
+    let unset_states: Vec<_> = state_params.iter().map(|_| &unset_marker_ident).collect();
+
+    let mut builder_generics = st.generics.clone();
+    for state_param in state_params.iter() {
+        builder_generics.params.push(syn::parse_quote!(#state_param = #unset_marker_ident));
+    }
+    let set_builder_states = state_params.iter().map(|_| &set_marker_ident);
+    let struct_to_builder_impls = struct_fields_generator::generate_to_builder_impls(
+        st,
+        &struct_builder_name_ident,
+        quote::quote!(#struct_builder_name_ident<#(#generic_args,)* #(#set_builder_states),*>),
+        quote::quote!(__state: ::core::marker::PhantomData,),
+    )?;
+    let phantom_type = struct_fields_generator::generate_builder_phantom_type(st);
+    let setters_generics = extend_generics(&st.generics, &state_params);
+    let (setters_impl_generics, _, _) = setters_generics.split_for_impl();
+    let builder_struct_attrs = struct_fields_generator::generate_builder_struct_attrs(st)?;
+    let set_marker_docs = struct_fields_generator::generate_doc(&format!("State of a required field of [`{}`] that has been set.", struct_builder_name_ident));
+    let unset_marker_docs = struct_fields_generator::generate_doc(&format!("State of a required field of [`{}`] that hasn't been set yet.", struct_builder_name_ident));
+    let struct_builder_docs = struct_fields_generator::generate_doc(&format!(
+        "Builder for [`{}`], handed out by [`{}`]. Each required field has a state param, which its setter turns from [`{}`] into [`{}`].\n\n{}",
+        super::get_path_doc_name(&target.constructor),
+        super::get_builder_fn_doc_name(target),
+        unset_marker_ident,
+        set_marker_ident,
+        fields_summary_doc
+    ));
+    let builder_fn_docs = struct_fields_generator::generate_doc(&format!("Creates a [`{}`] with no field set yet.\n\n{}", struct_builder_name_ident, fields_summary_doc));
+    let builder_fn = super::generate_builder_fn(st, target, &builder_fn_docs, &quote::quote!(#struct_builder_name_ident<#(#generic_args,)* #(#unset_states),*>))?;
+    Ok(quote::quote!(
+        // derives on the builder bound every state param, so the markers implement whatever could be derived
+        #[derive(::core::clone::Clone, ::core::marker::Copy, ::core::fmt::Debug, ::core::cmp::PartialEq, ::core::cmp::Eq, ::core::cmp::PartialOrd, ::core::cmp::Ord, ::core::hash::Hash)]
+        #set_marker_docs
+        #vis struct #set_marker_ident;
+        #[derive(::core::clone::Clone, ::core::marker::Copy, ::core::fmt::Debug, ::core::cmp::PartialEq, ::core::cmp::Eq, ::core::cmp::PartialOrd, ::core::cmp::Ord, ::core::hash::Hash)]
+        #unset_marker_docs
+        #vis struct #unset_marker_ident;
+
+        #struct_builder_docs
+        #[derive(#(#builder_derives),*)]
+        #builder_struct_attrs
+        #vis struct #struct_builder_name_ident #builder_generics #struct_where_clause {
+            #struct_fields_ref
+            __state: ::core::marker::PhantomData<(#(#state_params,)* #phantom_type)>,
+        }
+
+        #builder_error
+
+        impl #setters_impl_generics #struct_builder_name_ident<#(#generic_args,)* #(#state_params),*> #struct_where_clause {
+            #(#setter_methods)*
+        }
+
+        impl #struct_impl_generics #struct_builder_name_ident<#(#generic_args,)* #(#set_states),*> #struct_where_clause {
+            #build_method
+        }
+
+        impl #struct_impl_generics ::core::default::Default for #struct_builder_name_ident<#(#generic_args,)* #(#unset_states),*> #struct_where_clause {
+            fn default() -> Self {
+                #struct_builder_name_ident {
+                    #struct_builder_method_fileds_ref
+                    __state: ::core::marker::PhantomData,
+                }
+            }
+        }
+
+        #builder_fn
+
+        #struct_to_builder_impls
+    ))
+}
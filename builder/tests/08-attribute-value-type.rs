@@ -0,0 +1,22 @@
+// Every key takes one kind of value: nothing, a string, a path or a list.
+// Giving it the wrong kind, or a string that doesn't fit, is an error showing
+// how the key is meant to be written.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate = true, pattern = "shared", vis = 1, setter_prefix = "with-")]
+pub struct Command {
+    #[builder(each)]
+    args: Vec<String>,
+    #[builder(each = 3)]
+    env: Vec<String>,
+    #[builder(each = "not an ident")]
+    features: Vec<String>,
+    #[builder(default = 5, setter = "into")]
+    current_dir: Option<String>,
+    #[builder(validate = 3, computed = "1 +")]
+    retries: u8,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+// A key given twice is rejected at its second occurrence, whether both are in
+// the same #[builder(...)], in separate ones or inside setter(...).
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+#[builder(pattern = "mutable")]
+pub struct Command {
+    #[builder(each = "arg", each = "argument")]
+    args: Vec<String>,
+    #[builder(setter(into, into))]
+    current_dir: Option<String>,
+}
+
+fn main() {}
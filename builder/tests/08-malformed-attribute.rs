@@ -0,0 +1,19 @@
+// A #[builder] that can't be read at all is an error pointing at it, rather
+// than being ignored or panicking, and every one of them is reported in the
+// same compile.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder]
+    executable: String,
+    #[builder = "arg"]
+    args: Vec<String>,
+    #[builder(each = )]
+    env: Vec<String>,
+    #[builder(each = "x" "y")]
+    current_dir: Option<String>,
+}
+
+fn main() {}
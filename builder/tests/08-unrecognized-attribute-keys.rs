@@ -0,0 +1,15 @@
+// Every unknown key is reported, with the closest known key suggested when
+// one is close enough and the full list of keys otherwise.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typstate)]
+pub struct Command {
+    #[builder(eac = "arg")]
+    args: Vec<String>,
+    #[builder(setter(nmae = "directory"), frobnicate)]
+    current_dir: Option<String>,
+}
+
+fn main() {}
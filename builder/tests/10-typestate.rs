@@ -0,0 +1,52 @@
+// With #[builder(typestate)] on the struct, the builder tracks in its type
+// which required fields have been set. Setters take the builder by value and
+// return it with that field's state flipped from unset to set, and build() is
+// only available once every required field is set. Optional and repeated
+// fields may still be called any number of times, in any order.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+    max_retries: u32,
+}
+
+// `x` and `_x` are different fields, each with a state of its own
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Point {
+    x: u8,
+    _x: u8,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Token {
+    r#type: String,
+}
+
+fn main() {
+    let command = Command::builder()
+        .arg("build".to_owned())
+        .max_retries(3)
+        .current_dir("..".to_owned())
+        .executable("cargo".to_owned())
+        .arg("--release".to_owned())
+        .build();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.current_dir.unwrap(), "..");
+    assert_eq!(command.max_retries, 3);
+
+    let token = Token::builder().r#type("ident".to_owned()).build();
+    assert_eq!(token.r#type, "ident");
+
+    let point = Point::builder().x(1)._x(2).build();
+    assert_eq!((point.x, point._x), (1, 2));
+}
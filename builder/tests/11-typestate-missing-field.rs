@@ -0,0 +1,15 @@
+// A typestate builder reports a forgotten required field when the caller is
+// compiled, rather than returning an error from build() at runtime.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    max_retries: u32,
+}
+
+fn main() {
+    let _command = Command::builder().executable("cargo".to_owned()).build();
+}
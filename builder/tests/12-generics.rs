@@ -0,0 +1,50 @@
+// The builder of a generic struct carries the same lifetimes, type params,
+// const generics, bounds and where-clause as the struct it builds, so
+// borrowed views and generic containers can derive Builder too.
+
+use derive_builder::Builder;
+use std::fmt::Debug;
+
+#[derive(Builder)]
+pub struct View<'a, T: Clone, const N: usize>
+where
+    T: Debug,
+{
+    name: &'a str,
+    items: [T; N],
+    #[builder(each = "tag")]
+    tags: Vec<&'a str>,
+    parent: Option<&'a str>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Pair<'a, K, V = u32>
+where
+    K: Debug,
+{
+    key: &'a K,
+    value: V,
+    note: Option<String>,
+}
+
+fn main() {
+    let name = String::from("view");
+    let view: View<u8, 3> = View::builder()
+        .name(&name)
+        .items([1, 2, 3])
+        .tag("a")
+        .tag("b")
+        .build()
+        .unwrap();
+    assert_eq!(view.name, "view");
+    assert_eq!(view.items, [1, 2, 3]);
+    assert_eq!(view.tags, vec!["a", "b"]);
+    assert!(view.parent.is_none());
+
+    let key = String::from("answer");
+    let pair = Pair::builder().value(42).key(&key).build();
+    assert_eq!(pair.key, "answer");
+    assert_eq!(pair.value, 42);
+    assert!(pair.note.is_none());
+}
@@ -0,0 +1,68 @@
+// A field marked #[builder(default)] no longer has to be set before build();
+// when it is missing, build() falls back to Default::default(). With
+// #[builder(default = "...")] the string is parsed as an expression instead,
+// which may use any field declared before it. A struct-level
+// #[builder(default)] falls back to the struct's own Default impl for every
+// field that was not set.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Server {
+    host: String,
+    #[builder(default = "8080")]
+    port: u16,
+    #[builder(default = "format!(\"{}:{}\", host, port)")]
+    address: String,
+    #[builder(default)]
+    workers: usize,
+    #[builder(default = "Some(30)")]
+    timeout: Option<u64>,
+}
+
+#[derive(Builder)]
+#[builder(default)]
+pub struct Limits {
+    max_connections: u32,
+    max_body_size: u64,
+    name: String,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_connections: 128,
+            max_body_size: 1024,
+            name: "limits".to_owned(),
+        }
+    }
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Client {
+    host: String,
+    #[builder(default = "80")]
+    port: u16,
+}
+
+fn main() {
+    let server = Server::builder().host("localhost".to_owned()).build().unwrap();
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.address, "localhost:8080");
+    assert_eq!(server.workers, 0);
+    assert_eq!(server.timeout, Some(30));
+
+    let server = Server::builder().host("localhost".to_owned()).port(9000).workers(4).timeout(5).build().unwrap();
+    assert_eq!(server.address, "localhost:9000");
+    assert_eq!(server.workers, 4);
+    assert_eq!(server.timeout, Some(5));
+
+    let limits = Limits::builder().max_connections(16).build().unwrap();
+    assert_eq!(limits.max_connections, 16);
+    assert_eq!(limits.max_body_size, 1024);
+    assert_eq!(limits.name, "limits");
+
+    let client = Client::builder().host("localhost".to_owned()).build();
+    assert_eq!(client.port, 80);
+}
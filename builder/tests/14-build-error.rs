@@ -0,0 +1,59 @@
+// build() returns a generated CommandBuilderError which lists every missing
+// field at once instead of stopping at the first one. It implements Display
+// and std::error::Error, so it still converts into Box<dyn Error>.
+//
+// With #[builder(error = path::To::MyError)] build() returns the given error
+// type instead, converting the generated one through From.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+    max_retries: u32,
+}
+
+pub mod api {
+    #[derive(Debug)]
+    pub struct BadRequest {
+        pub missing: Vec<&'static str>,
+    }
+
+    impl From<super::RequestBuilderError> for BadRequest {
+        fn from(err: super::RequestBuilderError) -> Self {
+            match err {
+                super::RequestBuilderError::MissingFields(missing) => BadRequest { missing },
+                super::RequestBuilderError::Validation(_) => BadRequest { missing: Vec::new() },
+            }
+        }
+    }
+}
+
+#[derive(Builder)]
+#[builder(error = api::BadRequest)]
+pub struct Request {
+    method: String,
+    path: String,
+    body: Option<String>,
+}
+
+fn build_boxed() -> Result<Command, Box<dyn std::error::Error>> {
+    Ok(Command::builder().executable("cargo".to_owned()).max_retries(1).build()?)
+}
+
+fn main() {
+    let err = Command::builder().arg("build".to_owned()).build().err().unwrap();
+    match &err {
+        CommandBuilderError::MissingFields(fields) => assert_eq!(fields, &["executable", "max_retries"]),
+        CommandBuilderError::Validation(_) => unreachable!(),
+    }
+    assert_eq!(err.to_string(), "executable field is missing, max_retries field is missing");
+
+    assert!(build_boxed().is_ok());
+
+    let err: api::BadRequest = Request::builder().path("/".to_owned()).build().err().unwrap();
+    assert_eq!(err.missing, vec!["method"]);
+}
@@ -0,0 +1,65 @@
+// #[builder(pattern = "...")] selects how setters and build() take the builder.
+//
+//   - "mutable", the default: setters take &mut self and build() clones every
+//     field, so the builder can be reused.
+//   - "owned": setters take self by value and build(self) moves every field
+//     out, so none of the field types need to implement Clone.
+//   - "immutable": setters take &self and return a modified copy of the
+//     builder, which is handy for deriving several values from a common base.
+
+use derive_builder::Builder;
+use std::sync::mpsc::{self, Receiver};
+
+pub struct Handle {
+    id: u32,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Worker {
+    handle: Handle,
+    inbox: Receiver<String>,
+    #[builder(each = "tag")]
+    tags: Vec<String>,
+    name: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "immutable")]
+pub struct Endpoint {
+    host: String,
+    port: u16,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "mutable")]
+pub struct Counter {
+    start: u32,
+}
+
+fn main() {
+    let (sender, receiver) = mpsc::channel();
+    let worker = Worker::builder()
+        .handle(Handle { id: 7 })
+        .inbox(receiver)
+        .tag("io".to_owned())
+        .build()
+        .unwrap();
+    sender.send("ping".to_owned()).unwrap();
+    assert_eq!(worker.handle.id, 7);
+    assert_eq!(worker.inbox.recv().unwrap(), "ping");
+    assert_eq!(worker.tags, vec!["io"]);
+    assert!(worker.name.is_none());
+
+    let base = Endpoint::builder().host("localhost".to_owned());
+    let http = base.port(80).build().unwrap();
+    let https = base.port(443).build().unwrap();
+    assert_eq!(http.port, 80);
+    assert_eq!(https.port, 443);
+    assert_eq!(https.host, "localhost");
+
+    let mut counter = Counter::builder();
+    counter.start(1);
+    assert_eq!(counter.build().unwrap().start, 1);
+    assert_eq!(counter.start(2).build().unwrap().start, 2);
+}
@@ -0,0 +1,60 @@
+// #[builder(validate = path)] on the struct names a function taking the
+// builder, which build() calls once every required field is known to be set.
+// On a field it names a function taking a reference to that field's value,
+// defaults included. Any Err(message) is returned from build() as the
+// Validation variant of the builder error, before the struct is constructed.
+
+use derive_builder::Builder;
+
+fn check_range(builder: &RangeBuilder) -> Result<(), String> {
+    if builder.start > builder.end {
+        return Err("start must not be after end".to_owned());
+    }
+    Ok(())
+}
+
+fn check_step(step: &u32) -> Result<(), String> {
+    if *step == 0 {
+        return Err("must be positive".to_owned());
+    }
+    Ok(())
+}
+
+#[derive(Builder)]
+#[builder(validate = check_range)]
+pub struct Range {
+    start: u32,
+    end: u32,
+    #[builder(default = "1", validate = check_step)]
+    step: u32,
+}
+
+fn check_bounds(builder: &LimitBuilder<LimitBuilderSet>) -> Result<(), String> {
+    if builder.max == Some(0) {
+        return Err("max must not be zero".to_owned());
+    }
+    Ok(())
+}
+
+#[derive(Builder)]
+#[builder(typestate, validate = check_bounds)]
+pub struct Limit {
+    max: u32,
+}
+
+fn main() {
+    let range = Range::builder().start(1).end(5).build().unwrap();
+    assert_eq!(range.step, 1);
+
+    let err = Range::builder().start(5).end(1).build().err().unwrap();
+    assert_eq!(err.to_string(), "start must not be after end");
+
+    let err = Range::builder().start(1).end(5).step(0).build().err().unwrap();
+    assert_eq!(err.to_string(), "step: must be positive");
+
+    let err = Range::builder().build().err().unwrap();
+    assert!(matches!(err, RangeBuilderError::MissingFields(_)));
+
+    assert_eq!(Limit::builder().max(3).build().unwrap().max, 3);
+    assert!(Limit::builder().max(0).build().is_err());
+}
@@ -0,0 +1,55 @@
+// #[builder(setter(into))] makes a setter generic over anything that converts
+// into the field type, so `.executable("cargo")` works without to_owned().
+// On an `each` field it applies to the one-at-a-time setter.
+//
+// Every Option field additionally gets clear_<field>() and <field>_opt(..),
+// so callers can put a field back to None after having set it.
+
+use derive_builder::Builder;
+use std::path::PathBuf;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(setter(into))]
+    executable: String,
+    #[builder(each = "arg", setter(into))]
+    args: Vec<String>,
+    #[builder(setter(into))]
+    current_dir: Option<PathBuf>,
+    timeout: Option<u64>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Job {
+    #[builder(setter(into))]
+    name: String,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo")
+        .arg("build")
+        .arg(String::from("--release"))
+        .current_dir("/tmp")
+        .timeout(30)
+        .clear_timeout()
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.current_dir, Some(PathBuf::from("/tmp")));
+    assert_eq!(command.timeout, None);
+
+    let command = Command::builder()
+        .executable("cargo")
+        .current_dir("/tmp")
+        .current_dir_opt(None)
+        .timeout_opt(Some(5))
+        .build()
+        .unwrap();
+    assert_eq!(command.current_dir, None);
+    assert_eq!(command.timeout, Some(5));
+
+    assert_eq!(Job::builder().name("nightly").build().name, "nightly");
+}
@@ -0,0 +1,81 @@
+// `each` works with any collection implementing Default + Extend<Item>, not
+// just Vec. Maps get a one-at-a-time setter taking the key and the value as
+// separate arguments. Every `each` field also gets an extend_<field>(..) setter
+// accepting anything IntoIterator over the collection's items.
+//
+// Maps are told apart by name alone: a type named ...Map with two type args
+// is a map, any other collection is extended with its first type arg.
+
+use derive_builder::Builder;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+// ends in "map" but isn't one, it holds single bits
+#[derive(Clone, Default)]
+pub struct Bitmap<T>(Vec<T>);
+
+impl<T> Extend<T> for Bitmap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, items: I) {
+        self.0.extend(items);
+    }
+}
+
+impl<T> IntoIterator for Bitmap<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Bitmap<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "env", setter(into))]
+    envs: HashMap<String, String>,
+    #[builder(each = "feature")]
+    features: HashSet<&'static str>,
+    #[builder(each = "step")]
+    steps: VecDeque<u32>,
+    #[builder(each = "label")]
+    labels: BTreeMap<u8, char>,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(each = "bit")]
+    bits: Bitmap<u8>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .env("RUST_LOG", "debug")
+        .env("CARGO_TERM_COLOR".to_owned(), "always")
+        .feature("serde")
+        .feature("serde")
+        .step(1)
+        .extend_steps(vec![2, 3])
+        .label(2, 'b')
+        .extend_labels([(1, 'a')])
+        .extend_args(["build", "--release"].iter().map(|arg| arg.to_string()))
+        .arg("--locked".to_owned())
+        .bit(1)
+        .build()
+        .unwrap();
+
+    assert_eq!(command.envs["RUST_LOG"], "debug");
+    assert_eq!(command.envs["CARGO_TERM_COLOR"], "always");
+    assert_eq!(command.features.len(), 1);
+    assert_eq!(command.steps, VecDeque::from(vec![1, 2, 3]));
+    assert_eq!(command.labels.values().collect::<String>(), "ab");
+    assert_eq!(command.args, vec!["build", "--release", "--locked"]);
+    assert_eq!(command.bits.0, [1]);
+}
@@ -0,0 +1,60 @@
+// The builder, its setters, builder() and build() take the visibility of the
+// struct being built, so a builder derived in one module is usable from
+// another. #[builder(vis = "...")] overrides it, on the struct for the whole
+// builder and on a field for that field's setter. #[builder(private)] keeps a
+// setter private to the module of the struct.
+
+mod config {
+    use derive_builder::Builder;
+
+    #[derive(Builder)]
+    pub struct Server {
+        pub host: String,
+        pub port: u16,
+        #[builder(each = "alias")]
+        pub aliases: Vec<String>,
+        #[builder(vis = "pub(crate)")]
+        pub workers: Option<usize>,
+        #[builder(private)]
+        pub secret: Option<String>,
+    }
+
+    impl ServerBuilder {
+        pub fn with_generated_secret(&mut self) -> &mut Self {
+            self.secret("generated".to_owned())
+        }
+    }
+
+    #[derive(Builder)]
+    #[builder(vis = "pub(crate)")]
+    pub struct Limits {
+        pub max_body_size: u64,
+    }
+
+    #[derive(Builder)]
+    #[builder(typestate)]
+    pub struct Client {
+        pub host: String,
+    }
+}
+
+fn main() {
+    let server = config::Server::builder()
+        .host("localhost".to_owned())
+        .port(8080)
+        .alias("local".to_owned())
+        .workers(4)
+        .with_generated_secret()
+        .build()
+        .unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.aliases, vec!["local"]);
+    assert_eq!(server.workers, Some(4));
+    assert_eq!(server.secret.as_deref(), Some("generated"));
+
+    let limits = config::Limits::builder().max_body_size(1024).build().unwrap();
+    assert_eq!(limits.max_body_size, 1024);
+
+    let client = config::Client::builder().host("localhost".to_owned()).build();
+    assert_eq!(client.host, "localhost");
+}
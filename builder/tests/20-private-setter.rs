@@ -0,0 +1,17 @@
+// A setter marked #[builder(private)] is not callable from outside the module
+// that derives the builder.
+
+mod config {
+    use derive_builder::Builder;
+
+    #[derive(Builder)]
+    pub struct Server {
+        pub host: String,
+        #[builder(private)]
+        pub secret: Option<String>,
+    }
+}
+
+fn main() {
+    let _server = config::Server::builder().host("localhost".to_owned()).secret("hunter2".to_owned()).build();
+}
@@ -0,0 +1,44 @@
+// #[builder(to_builder)] generates From<Config> for ConfigBuilder and
+// Config::to_builder(&self), both of which pre-populate every field of the
+// builder from an existing value, so callers can change a couple of fields and
+// build again.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(to_builder)]
+pub struct Config<T: Clone> {
+    name: String,
+    value: T,
+    #[builder(each = "tag")]
+    tags: Vec<String>,
+    parent: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(typestate, to_builder)]
+pub struct Endpoint {
+    host: String,
+    port: u16,
+}
+
+fn main() {
+    let base = Config::builder().name("base".to_owned()).value(1).tag("a".to_owned()).build().unwrap();
+
+    let derived = base.to_builder().value(2).tag("b".to_owned()).build().unwrap();
+    assert_eq!(derived.name, "base");
+    assert_eq!(derived.value, 2);
+    assert_eq!(derived.tags, vec!["a", "b"]);
+    assert_eq!(derived.parent, None);
+
+    let mut builder = ConfigBuilder::from(derived);
+    let rebuilt = builder.parent("base".to_owned()).build().unwrap();
+    assert_eq!(rebuilt.parent.as_deref(), Some("base"));
+    assert_eq!(rebuilt.value, 2);
+
+    let endpoint = Endpoint::builder().host("localhost".to_owned()).port(80).build();
+    let secure = endpoint.to_builder().port(443).build();
+    assert_eq!(secure.host, "localhost");
+    assert_eq!(secure.port, 443);
+    assert_eq!(EndpointBuilder::from(secure).build().port, 443);
+}
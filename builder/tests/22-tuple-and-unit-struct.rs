@@ -0,0 +1,33 @@
+// Tuple structs get positional setters, named field0, field1, ... unless a
+// field carries #[builder(name = "...")]. Unit structs get a trivial builder
+// whose build() always succeeds.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Point(i32, i32, Option<String>);
+
+#[derive(Builder)]
+pub struct Span(#[builder(name = "start")] usize, #[builder(name = "end", default = "start + 1")] usize);
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Meters(#[builder(name = "value")] f64);
+
+#[derive(Builder)]
+pub struct Marker;
+
+fn main() {
+    let point = Point::builder().field0(1).field1(2).build().unwrap();
+    assert_eq!((point.0, point.1, point.2), (1, 2, None));
+
+    let span = Span::builder().start(3).build().unwrap();
+    assert_eq!((span.0, span.1), (3, 4));
+
+    let err = Span::builder().build().err().unwrap();
+    assert_eq!(err.to_string(), "start field is missing");
+
+    assert_eq!(Meters::builder().value(1.5).build().0, 1.5);
+
+    let Marker = Marker::builder().build().unwrap();
+}
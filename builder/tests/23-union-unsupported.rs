@@ -0,0 +1,12 @@
+// Deriving Builder on something other than a struct reports what is actually
+// wrong with the input.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+// Every variant gets a builder of its own, so naming the builder or its
+// constructor only works on a variant, never on the enum as a whole.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(name = "EventBuilder")]
+pub enum Event {
+    Created { id: u64 },
+    Deleted { id: u64 },
+}
+
+fn main() {}
@@ -0,0 +1,52 @@
+// Deriving Builder on an enum generates one builder per variant, handed out
+// by a snake_case constructor named after the variant. Each builder behaves
+// exactly like the builder of a struct with the variant's fields, and its
+// build() returns the enum.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Event {
+    Created {
+        id: u64,
+        owner: String,
+        #[builder(each = "tag")]
+        tags: Vec<String>,
+        note: Option<String>,
+    },
+    Deleted(#[builder(name = "id")] u64),
+    Reset,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub enum Shape<T> {
+    Circle { radius: T },
+    Empty,
+}
+
+fn main() {
+    let event = Event::created_builder().id(1).owner("alice".to_owned()).tag("new".to_owned()).build().unwrap();
+    assert_eq!(
+        event,
+        Event::Created {
+            id: 1,
+            owner: "alice".to_owned(),
+            tags: vec!["new".to_owned()],
+            note: None,
+        }
+    );
+
+    let mut builder: EventCreatedBuilder = Event::created_builder();
+    let err = builder.build().err().unwrap();
+    assert_eq!(err.to_string(), "id field is missing, owner field is missing");
+
+    assert_eq!(Event::deleted_builder().id(7).build().unwrap(), Event::Deleted(7));
+    assert_eq!(Event::reset_builder().build().unwrap(), Event::Reset);
+
+    match Shape::circle_builder().radius(2.0).build() {
+        Shape::Circle { radius } => assert_eq!(radius, 2.0),
+        Shape::Empty => unreachable!(),
+    }
+    let _: Shape<u8> = Shape::empty_builder().build();
+}
@@ -0,0 +1,20 @@
+// An immutable outer builder only has `&self` in build(), so it builds a
+// clone of the nested builder, which then has to #[derive(Clone)] through
+// #[builder(derive(Clone))]. The error points at the field's type rather
+// than into the generated code.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Server {
+    host: String,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "immutable")]
+pub struct Gateway {
+    #[builder(sub_builder)]
+    server: Server,
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+// sub_builder only sees the field's type, so it expects the nested builder
+// exactly as derived by default: named `{Type}Builder`, failing with
+// `{Type}BuilderError`, built by `build()` with the mutable pattern. A nested
+// struct that changes any of that with `name`, `error`, `build_fn(name)`,
+// `pattern` or `typestate` is reported at the field's type.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Server {
+    host: String,
+}
+
+#[derive(Builder)]
+pub struct Gateway {
+    #[builder(sub_builder)]
+    server: Server,
+}
+
+fn main() {}
@@ -0,0 +1,92 @@
+// A field whose type derives Builder can be marked #[builder(sub_builder)],
+// which stores that type's builder inside the outer one. The setter takes a
+// closure over the nested builder, and build() builds the nested value too,
+// reporting its missing fields under the field's name in MissingNestedFields.
+//
+// The nested builder has to be derived the default way, see
+// 25-sub-builder-owned.rs for what happens otherwise.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Database {
+    url: String,
+    #[builder(sub_builder)]
+    admin: Server,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct ServiceConfig {
+    name: String,
+    #[builder(sub_builder)]
+    server: Server,
+    #[builder(sub_builder)]
+    database: Database,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(pattern = "owned", validate = check_ports)]
+pub struct Proxy {
+    #[builder(sub_builder)]
+    listen: Server,
+    #[builder(sub_builder)]
+    upstream: Server,
+}
+
+// the nested builders are still in place when the struct-level validator runs
+fn check_ports(builder: &ProxyBuilder) -> Result<(), String> {
+    if builder.listen.port == builder.upstream.port {
+        return Err("listen and upstream ports must differ".to_owned());
+    }
+    Ok(())
+}
+
+fn main() {
+    let config = ServiceConfig::builder()
+        .name("api".to_owned())
+        .server(|server| server.host("0.0.0.0".to_owned()).port(8080))
+        .database(|database| database.url("postgres://db".to_owned()).admin(|admin| admin.host("localhost".to_owned()).port(5432)))
+        .build()
+        .unwrap();
+    assert_eq!(config.server, Server { host: "0.0.0.0".to_owned(), port: 8080 });
+    assert_eq!(config.database.admin.port, 5432);
+
+    let mut builder = ServiceConfig::builder();
+    builder.server(|server| server.host("0.0.0.0".to_owned()));
+    builder.database_mut().admin_mut().port(5432);
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.to_string(), "name field is missing, server.port field is missing, database.url field is missing, database.admin.host field is missing");
+    match &err {
+        ServiceConfigBuilderError::MissingNestedFields(paths) => assert_eq!(paths, &["name", "server.port", "database.url", "database.admin.host"]),
+        _ => unreachable!(),
+    }
+
+    // as long as the nested builders are complete only the outer fields are missing, by their plain name
+    let mut builder = ServiceConfig::builder();
+    builder.server(|server| server.host("0.0.0.0".to_owned()).port(8080));
+    builder.database(|database| database.url("postgres://db".to_owned()).admin(|admin| admin.host("localhost".to_owned()).port(5432)));
+    match builder.build().unwrap_err() {
+        ServiceConfigBuilderError::MissingFields(fields) => assert_eq!(fields, vec!["name"]),
+        _ => unreachable!(),
+    }
+
+    let proxy = Proxy::builder()
+        .listen(|listen| listen.host("0.0.0.0".to_owned()).port(80))
+        .upstream(|upstream| upstream.host("backend".to_owned()).port(8080))
+        .build()
+        .unwrap();
+    assert_eq!(proxy.upstream.port, 8080);
+
+    let err = Proxy::builder()
+        .listen(|listen| listen.host("0.0.0.0".to_owned()).port(80))
+        .upstream(|upstream| upstream.host("backend".to_owned()).port(80))
+        .build()
+        .unwrap_err();
+    assert_eq!(err.to_string(), "listen and upstream ports must differ");
+}
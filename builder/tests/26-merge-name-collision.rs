@@ -0,0 +1,13 @@
+// merge() and is_<field>_set() live next to the setters, so a field whose
+// setter would take one of their names has to be renamed with
+// #[builder(setter(name = "..."))].
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Git {
+    merge: bool,
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,62 @@
+// merge() lays another builder over this one, the way configuration is
+// layered from defaults, files, environment and flags. Fields set in the
+// other builder win, `each` collections are appended to unless marked
+// #[builder(merge = "replace")], and nested builders are merged field by
+// field. is_<field>_set() tells which fields have been set so far.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Config {
+    name: String,
+    log_level: Option<String>,
+    #[builder(each = "plugin")]
+    plugins: Vec<String>,
+    #[builder(each = "origin", merge = "replace")]
+    allowed_origins: Vec<String>,
+    #[builder(sub_builder)]
+    server: Server,
+}
+
+// a field called `merge` keeps its name, only its setter has to make room for merge()
+#[derive(Builder)]
+pub struct Git {
+    #[builder(setter(name = "no_ff"))]
+    merge: bool,
+}
+
+fn main() {
+    let mut defaults = Config::builder();
+    defaults.name("service".to_owned()).log_level("info".to_owned()).plugin("metrics".to_owned()).origin("*".to_owned());
+    defaults.server(|server| server.host("127.0.0.1".to_owned()).port(80));
+
+    let mut file = Config::builder();
+    file.plugin("tracing".to_owned()).origin("https://example.com".to_owned());
+    file.server(|server| server.port(8080));
+
+    let mut flags = Config::builder();
+    flags.log_level("debug".to_owned());
+    assert!(flags.is_log_level_set());
+    assert!(!flags.is_name_set());
+    assert!(!flags.is_plugins_set());
+
+    let config = defaults.merge(file).merge(flags).build().unwrap();
+    assert_eq!(config.name, "service");
+    assert_eq!(config.log_level.as_deref(), Some("debug"));
+    assert_eq!(config.plugins, ["metrics", "tracing"]);
+    assert_eq!(config.allowed_origins, ["https://example.com"]);
+    assert_eq!(config.server, Server { host: "127.0.0.1".to_owned(), port: 8080 });
+
+    let mut flags = Git::builder();
+    flags.no_ff(true);
+    let mut git = Git::builder();
+    git.merge(flags);
+    assert!(git.is_merge_set());
+    assert!(git.build().unwrap().merge);
+}
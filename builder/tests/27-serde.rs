@@ -0,0 +1,56 @@
+// With the `serde` feature, #[builder(derive(Serialize, Deserialize))] puts
+// those derives on the builder. Every field of the builder is optional in the
+// document, so a partial config file can be loaded into a builder, merged with
+// other sources and validated by build(). #[serde(rename)] and
+// #[serde(rename_all)] on the struct carry over to the builder.
+
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+
+#[derive(Builder, Deserialize, Debug, PartialEq)]
+#[builder(derive(Deserialize))]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+#[derive(Builder, Serialize, Deserialize, Debug)]
+#[builder(derive(Serialize, Deserialize))]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    service_name: String,
+    #[serde(rename = "level")]
+    log_level: Option<String>,
+    #[builder(each = "plugin")]
+    plugins: Vec<String>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(derive(Deserialize))]
+pub struct Deployment {
+    replicas: u32,
+    #[builder(sub_builder)]
+    server: Server,
+}
+
+fn main() {
+    let mut file: ConfigBuilder = serde_json::from_str(r#"{ "service-name": "api", "plugins": ["metrics"] }"#).unwrap();
+    let flags: ConfigBuilder = serde_json::from_str(r#"{ "level": "debug" }"#).unwrap();
+    let config = file.merge(flags).build().unwrap();
+    assert_eq!(config.service_name, "api");
+    assert_eq!(config.log_level.as_deref(), Some("debug"));
+    assert_eq!(config.plugins, ["metrics"]);
+
+    let partial: ConfigBuilder = serde_json::from_str("{}").unwrap();
+    assert_eq!(serde_json::to_string(&partial).unwrap(), r#"{"service-name":null,"level":null,"plugins":[]}"#);
+
+    let mut deployment: DeploymentBuilder = serde_json::from_str(r#"{ "replicas": 3, "server": { "host": "0.0.0.0" } }"#).unwrap();
+    let err = deployment.build().unwrap_err();
+    assert_eq!(err.to_string(), "server.port field is missing");
+    let deployment = deployment.server(|server| server.port(8080)).build().unwrap();
+    assert_eq!(deployment.replicas, 3);
+    assert_eq!(deployment.server, Server { host: "0.0.0.0".to_owned(), port: 8080 });
+
+    let mut empty: DeploymentBuilder = serde_json::from_str(r#"{ "replicas": 1 }"#).unwrap();
+    assert_eq!(empty.build().unwrap_err().to_string(), "server.host field is missing, server.port field is missing");
+}
@@ -0,0 +1,13 @@
+// Without the `serde` feature of derive_builder, asking for serde derives on
+// the builder is a compile error pointing at the feature rather than a
+// confusing failure inside the generated code.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(derive(Deserialize))]
+pub struct Command {
+    executable: String,
+}
+
+fn main() {}
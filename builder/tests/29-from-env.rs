@@ -0,0 +1,59 @@
+// #[builder(env_prefix = "APP_")] generates FooBuilder::from_env(), which
+// reads APP_<FIELD> for every field through FromStr. `each` fields are split
+// on `env_separator`, a comma unless given, and maps take key=value items.
+// Fields marked #[builder(env_skip)] are never read, so they need no
+// FromStr. Variables that don't parse are all reported at once, each with its
+// field, in the InvalidEnvVars error.
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[derive(Builder, Debug)]
+#[builder(env_prefix = "APP_")]
+pub struct ServiceConfig {
+    host: String,
+    port: u16,
+    workers: Option<usize>,
+    #[builder(default = "false")]
+    debug: bool,
+    #[builder(each = "feature", env_separator = ":")]
+    features: Vec<String>,
+    #[builder(each = "label")]
+    labels: HashMap<String, u8>,
+    #[builder(env_skip, default = "Duration::from_secs(30)")]
+    timeout: Duration,
+}
+
+fn main() {
+    std::env::set_var("APP_HOST", "0.0.0.0");
+    std::env::set_var("APP_PORT", "8080");
+    std::env::set_var("APP_FEATURES", "auth:metrics");
+    std::env::set_var("APP_LABELS", "tier=1, zone=3");
+
+    let config = ServiceConfigBuilder::from_env().unwrap().build().unwrap();
+    assert_eq!(config.host, "0.0.0.0");
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.workers, None);
+    assert!(!config.debug);
+    assert_eq!(config.features, ["auth", "metrics"]);
+    assert_eq!(config.labels, HashMap::from([("tier".to_owned(), 1), ("zone".to_owned(), 3)]));
+    assert_eq!(config.timeout, Duration::from_secs(30));
+
+    // environment first, anything set afterwards overrides it
+    let config = ServiceConfigBuilder::from_env().unwrap().port(9090).workers(4).build().unwrap();
+    assert_eq!((config.port, config.workers), (9090, Some(4)));
+
+    std::env::remove_var("APP_HOST");
+    let err = ServiceConfigBuilder::from_env().unwrap().build().unwrap_err();
+    assert_eq!(err.to_string(), "host field is missing");
+
+    std::env::set_var("APP_PORT", "http");
+    std::env::set_var("APP_LABELS", "tier");
+    let err = ServiceConfigBuilder::from_env().err().unwrap();
+    assert_eq!(err.to_string(), "APP_PORT: invalid digit found in string, APP_LABELS: expected `key=value`, found `tier`");
+    match err {
+        ServiceConfigBuilderError::InvalidEnvVars(fields) => assert_eq!(fields.iter().map(|(field, _)| *field).collect::<Vec<_>>(), ["port", "labels"]),
+        _ => unreachable!(),
+    }
+}
@@ -0,0 +1,43 @@
+// The generated names can all be changed: the builder struct with
+// #[builder(name = "...")], the function handing out a builder with
+// `constructor`, the build method with `build_fn(name = "...")`, every setter
+// at once with `setter_prefix` and a single setter with `setter(name = "...")`.
+// The error type follows the builder's name.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(name = "CommandOptions", constructor = "options", build_fn(name = "finish"), setter_prefix = "with_")]
+pub struct Command {
+    executable: String,
+    #[builder(setter(name = "arguments"), each = "arg")]
+    args: Vec<String>,
+    #[builder(setter(name = "cwd", into))]
+    current_dir: Option<String>,
+}
+
+mod other {
+    use derive_builder::Builder;
+
+    // a second `Command` next to the first would collide on `CommandBuilder` without a name of its own
+    #[derive(Builder)]
+    #[builder(name = "RemoteCommandBuilder", typestate, build_fn(name = "finish"), setter_prefix = "with_")]
+    pub struct Command {
+        pub host: String,
+    }
+}
+
+fn main() {
+    let mut options: CommandOptions = Command::options();
+    let command = options.with_executable("cargo".to_owned()).arg("build".to_owned()).cwd("/tmp").finish().unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, ["build"]);
+    assert_eq!(command.current_dir.as_deref(), Some("/tmp"));
+
+    let command = Command::options().arguments(vec!["test".to_owned()]).finish();
+    let err: CommandOptionsError = command.err().unwrap();
+    assert_eq!(err.to_string(), "executable field is missing");
+
+    let remote: other::Command = other::Command::builder().with_host("example.com".to_owned()).finish();
+    assert_eq!(remote.host, "example.com");
+}
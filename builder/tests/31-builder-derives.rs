@@ -0,0 +1,53 @@
+// #[builder(derive(...))] derives traits onto the builder struct and
+// #[builder(struct_attr(...))] puts any other attribute on it. Every builder
+// implements Default, which is the same as a fresh builder().
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, Clone, PartialEq)]
+#[builder(derive(Debug, Clone, PartialEq), struct_attr(must_use = "a builder does nothing until built"))]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(derive(Debug, Clone), typestate)]
+pub struct Request {
+    url: String,
+    retries: Option<u32>,
+}
+
+#[derive(Builder)]
+#[builder(derive(Debug, Clone), pattern = "immutable")]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    builder.executable("cargo".to_owned()).arg("build".to_owned());
+    let snapshot = builder.clone();
+    builder.arg("--release".to_owned());
+    assert_ne!(snapshot, builder);
+    assert_eq!(
+        format!("{:?}", snapshot),
+        r#"CommandBuilder { executable: Some("cargo"), args: ["build"], current_dir: None }"#,
+    );
+
+    let defaulted = CommandBuilder::default();
+    assert_eq!(defaulted, Command::builder());
+
+    let request = Request::builder().url("https://example.com".to_owned());
+    let copy = request.clone();
+    assert!(format!("{:?}", copy).starts_with(r#"RequestBuilder { url: Some("https://example.com"), retries: None"#));
+    assert_eq!(copy.build().url, "https://example.com");
+    let _ = RequestBuilder::default().url(String::new()).build();
+
+    let origin = Point::builder().x(0);
+    assert_eq!(origin.y(1).build().unwrap().y, 1);
+    assert_eq!(format!("{:?}", origin), "PointBuilder { x: Some(0), y: None }");
+}
@@ -0,0 +1,63 @@
+// Everything the derive generates is documented, so crates that deny
+// missing_docs can use it. Doc comments on a field are copied onto the
+// setters standing in for it, the others get generated docs.
+
+#![deny(missing_docs)]
+//! Service configuration.
+
+use derive_builder::Builder;
+
+/// Where the service listens.
+#[derive(Builder)]
+#[builder(to_builder)]
+pub struct Server {
+    /// Host name or address to bind.
+    pub host: String,
+    /// Port to bind.
+    pub port: u16,
+}
+
+/// The whole configuration.
+#[derive(Builder)]
+#[builder(env_prefix = "APP_")]
+pub struct Config {
+    /// Name reported in logs.
+    pub name: String,
+    /// Log level, `info` when not set.
+    pub log_level: Option<String>,
+    /// Plugins loaded on startup.
+    #[builder(each = "plugin")]
+    pub plugins: Vec<String>,
+    /// Listening address.
+    #[builder(sub_builder)]
+    pub server: Server,
+}
+
+/// A request whose required fields are checked at compile time.
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Request {
+    /// Target URL.
+    pub url: String,
+    /// Retries on failure.
+    pub retries: Option<u32>,
+}
+
+/// A message sent between services.
+#[derive(Builder)]
+pub enum Message {
+    /// Announces a new service.
+    Hello {
+        /// Name of the service.
+        name: String,
+    },
+    /// Says goodbye.
+    Bye,
+}
+
+fn main() {
+    let config = Config::builder().name("api".to_owned()).plugin("metrics".to_owned()).server(|server| server.host("::".to_owned()).port(80)).build().unwrap();
+    assert_eq!(config.plugins, ["metrics"]);
+    assert_eq!(Request::builder().url("/".to_owned()).build().retries, None);
+    assert!(matches!(Message::bye_builder().build(), Ok(Message::Bye)));
+}
@@ -0,0 +1,52 @@
+// #[builder(try_setter)] adds try_<field>, which takes anything TryInto the
+// field's type and gives back the conversion error instead of setting the
+// field when it fails.
+
+use derive_builder::Builder;
+use std::convert::TryFrom;
+use std::num::TryFromIntError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Percent(u8);
+
+impl TryFrom<i64> for Percent {
+    type Error = String;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        match u8::try_from(value) {
+            Ok(value) if value <= 100 => Ok(Percent(value)),
+            _ => Err(format!("{} is not a percentage", value)),
+        }
+    }
+}
+
+#[derive(Builder, Debug)]
+pub struct Limits {
+    #[builder(try_setter)]
+    port: u16,
+    #[builder(try_setter)]
+    cpu: Option<Percent>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate, setter_prefix = "with_")]
+pub struct Listener {
+    #[builder(try_setter)]
+    port: u16,
+}
+
+fn main() -> Result<(), TryFromIntError> {
+    let mut builder = Limits::builder();
+    builder.try_port(8080_i64)?.try_cpu(50).unwrap();
+    let limits = builder.build().unwrap();
+    assert_eq!((limits.port, limits.cpu), (8080, Some(Percent(50))));
+
+    let err = Limits::builder().try_port(-1_i32).err().unwrap();
+    assert_eq!(err.to_string(), "out of range integral type conversion attempted");
+    let err = Limits::builder().try_cpu(250).err().unwrap();
+    assert_eq!(err, "250 is not a percentage");
+
+    let listener = Listener::builder().try_with_port(443_u64)?.build();
+    assert_eq!(listener.port, 443);
+    Ok(())
+}
@@ -0,0 +1,9 @@
+// The builder keeps every param as a field until call(), so a param borrowing
+// with an elided lifetime has to name it instead.
+
+#[derive_builder::builder]
+fn greet(name: &str, greeting: std::borrow::Cow<'_, str>) -> String {
+    format!("{}, {}!", greeting, name)
+}
+
+fn main() {}
@@ -0,0 +1,47 @@
+// #[derive_builder::builder] on a function generates a builder for its
+// arguments: connect_builder().host(..).port(..).call() calls connect() with
+// them. Params are classified like struct fields, Option params are optional
+// and #[builder(each = "..")] collects one item at a time.
+
+use std::time::Duration;
+
+#[derive(Debug, PartialEq)]
+pub struct Conn {
+    addr: String,
+    timeout: Option<Duration>,
+    tags: Vec<String>,
+}
+
+#[derive_builder::builder]
+pub fn connect(host: String, port: u16, timeout: Option<Duration>, #[builder(each = "tag")] tags: Vec<String>) -> Conn {
+    Conn {
+        addr: format!("{}:{}", host, port),
+        timeout,
+        tags,
+    }
+}
+
+#[derive_builder::builder(typestate)]
+fn sum<T: std::ops::Add<Output = T>>(mut lhs: T, rhs: T) -> T {
+    lhs = lhs + rhs;
+    lhs
+}
+
+fn main() {
+    let conn = connect_builder().host("localhost".to_owned()).port(5432).tag("primary".to_owned()).call().unwrap();
+    assert_eq!(
+        conn,
+        Conn {
+            addr: "localhost:5432".to_owned(),
+            timeout: None,
+            tags: vec!["primary".to_owned()],
+        }
+    );
+
+    let err = connect_builder().host("localhost".to_owned()).call().unwrap_err();
+    assert_eq!(err.to_string(), "port field is missing");
+
+    assert_eq!(connect("db".to_owned(), 1, Some(Duration::from_secs(1)), vec![]).timeout, Some(Duration::from_secs(1)));
+
+    assert_eq!(sum_builder().lhs(1).rhs(2).call(), 3);
+}
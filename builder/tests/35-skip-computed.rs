@@ -0,0 +1,47 @@
+// #[builder(skip)] leaves a field out of the builder, build() fills it in
+// from its `default` or Default. #[builder(computed = "expr")] derives a field
+// from the other fields once they are all known.
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+
+#[derive(Builder, Debug)]
+#[builder(to_builder)]
+pub struct Catalog {
+    name: String,
+    #[builder(each = "item")]
+    items: Vec<String>,
+    #[builder(skip)]
+    hits: u64,
+    #[builder(skip, default = "format!(\"catalog:{}\", name)")]
+    cache_key: String,
+    #[builder(computed = "items.iter().enumerate().map(|(idx, item)| (item.clone(), idx)).collect()")]
+    index: HashMap<String, usize>,
+    #[builder(computed = "index.len()")]
+    len: usize,
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate)]
+pub struct Rect {
+    width: u32,
+    height: u32,
+    #[builder(computed = "width * height")]
+    area: u32,
+}
+
+fn main() {
+    let mut builder = Catalog::builder();
+    builder.name("books".to_owned()).item("dune".to_owned()).item("emma".to_owned());
+    let catalog = builder.build().unwrap();
+    assert_eq!(catalog.hits, 0);
+    assert_eq!(catalog.cache_key, "catalog:books");
+    assert_eq!(catalog.index["emma"], 1);
+    assert_eq!(catalog.len, 2);
+
+    let catalog = catalog.to_builder().item("odyssey".to_owned()).build().unwrap();
+    assert_eq!(catalog.len, 3);
+
+    let rect = Rect::builder().width(3).height(4).build();
+    assert_eq!(rect.area, 12);
+}
@@ -0,0 +1,49 @@
+// Option fields are recognized by their full path as well, std::option::Option
+// or core::option::Option, but not a user type that is merely named Option.
+// #[builder(optional)] marks a field whose type hides an Option, like an
+// alias, and #[builder(required)] makes an Option field an ordinary one.
+// Option<Option<T>> fields keep "never set" apart from "set to None".
+
+extern crate alloc;
+
+use derive_builder::Builder;
+
+mod wrapper {
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct Option<T>(pub T);
+}
+
+type Maybe<T> = Option<T>;
+
+#[derive(Builder, Debug)]
+pub struct Request {
+    path: core::option::Option<String>,
+    retries: std::option::Option<u8>,
+    #[builder(optional)]
+    port: Maybe<u16>,
+    #[builder(required)]
+    tag: Option<String>,
+    wrapped: wrapper::Option<u8>,
+    limit: Option<Option<u32>>,
+    #[builder(each = "header")]
+    headers: alloc::vec::Vec<String>,
+}
+
+fn main() {
+    let mut builder = Request::builder();
+    builder.tag(None).wrapped(wrapper::Option(1));
+    let request = builder.build().unwrap();
+    assert_eq!((request.path, request.retries, request.port), (None, None, None));
+    assert_eq!((request.tag, request.wrapped, request.limit), (None, wrapper::Option(1), None));
+    assert!(request.headers.is_empty());
+
+    let mut builder = Request::builder();
+    builder.path("/".to_owned()).retries(3).port(Some(8080)).tag(Some("v1".to_owned())).wrapped(wrapper::Option(2)).limit(None).header("Accept".to_owned());
+    let request = builder.build().unwrap();
+    assert_eq!((request.path.as_deref(), request.retries, request.port), (Some("/"), Some(3), Some(8080)));
+    assert_eq!((request.tag.as_deref(), request.limit), (Some("v1"), Some(None)));
+    assert_eq!(request.headers, ["Accept"]);
+
+    let err = Request::builder().limit(Some(5)).build().unwrap_err();
+    assert_eq!(err.to_string(), "tag field is missing, wrapped field is missing");
+}
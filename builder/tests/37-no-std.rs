@@ -0,0 +1,49 @@
+// With the `std` feature turned off the generated code only refers to ::core
+// and ::alloc, so builders work in #![no_std] crates that have an allocator.
+
+#![no_std]
+
+extern crate alloc;
+// only for the test itself to run, under a name that keeps `::std` paths from resolving
+extern crate std as test_runtime;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use derive_builder::Builder;
+
+fn check_channel(channel: &u8) -> Result<(), String> {
+    if *channel < 16 {
+        Ok(())
+    } else {
+        Err("only 16 channels".to_string())
+    }
+}
+
+#[derive(Builder, Debug)]
+pub struct Radio {
+    #[builder(validate = check_channel)]
+    channel: u8,
+    power: Option<i8>,
+    #[builder(each = "peer")]
+    peers: Vec<u16>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate)]
+pub struct Pin {
+    number: u8,
+}
+
+fn main() {
+    let mut builder = Radio::builder();
+    builder.channel(3).peer(7);
+    let radio = builder.build().unwrap();
+    assert_eq!((radio.channel, radio.power, radio.peers.len()), (3, None, 1));
+
+    let err = Radio::builder().build().unwrap_err();
+    assert_eq!(err.to_string(), "channel field is missing");
+    let err: &dyn core::error::Error = &Radio::builder().channel(20).build().unwrap_err();
+    assert_eq!(err.to_string(), "channel: only 16 channels");
+
+    assert_eq!(Pin::builder().number(13).build().number, 13);
+}
@@ -1,6 +1,12 @@
 #[test]
 fn tests() {
     let t = trybuild::TestCases::new();
+    // without the `std` feature generated code needs `extern crate alloc`, which only the no_std test declares;
+    // CI runs it with `cargo test --no-default-features` in a step of its own
+    if cfg!(not(feature = "std")) {
+        t.pass("tests/37-no-std.rs");
+        return;
+    }
     t.pass("tests/01-parse.rs");
     t.pass("tests/02-create-builder.rs");
     t.pass("tests/03-call-setters.rs");
@@ -9,5 +15,44 @@ fn tests() {
     t.pass("tests/06-optional-field.rs");
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
+    t.compile_fail("tests/08-unrecognized-attribute-keys.rs");
+    t.compile_fail("tests/08-malformed-attribute.rs");
+    t.compile_fail("tests/08-duplicate-attribute-key.rs");
+    t.compile_fail("tests/08-attribute-value-type.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-typestate.rs");
+    t.compile_fail("tests/11-typestate-missing-field.rs");
+    t.pass("tests/12-generics.rs");
+    t.pass("tests/13-default.rs");
+    t.pass("tests/14-build-error.rs");
+    t.pass("tests/15-pattern.rs");
+    t.pass("tests/16-validate.rs");
+    t.pass("tests/17-setter-into.rs");
+    t.pass("tests/18-each-collections.rs");
+    t.pass("tests/19-visibility.rs");
+    t.compile_fail("tests/20-private-setter.rs");
+    t.pass("tests/21-to-builder.rs");
+    t.pass("tests/22-tuple-and-unit-struct.rs");
+    t.compile_fail("tests/23-union-unsupported.rs");
+    t.pass("tests/24-enum-builder.rs");
+    t.compile_fail("tests/24-enum-builder-name.rs");
+    t.pass("tests/25-sub-builder.rs");
+    t.compile_fail("tests/25-sub-builder-immutable.rs");
+    t.compile_fail("tests/25-sub-builder-owned.rs");
+    t.pass("tests/26-merge.rs");
+    t.compile_fail("tests/26-merge-name-collision.rs");
+    // only run by `cargo test --features serde`, which CI does in a step of its own
+    #[cfg(feature = "serde")]
+    t.pass("tests/27-serde.rs");
+    #[cfg(not(feature = "serde"))]
+    t.compile_fail("tests/28-serde-feature-disabled.rs");
+    t.pass("tests/29-from-env.rs");
+    t.pass("tests/30-custom-names.rs");
+    t.pass("tests/31-builder-derives.rs");
+    t.pass("tests/32-missing-docs.rs");
+    t.pass("tests/33-try-setter.rs");
+    t.pass("tests/34-fn-builder.rs");
+    t.compile_fail("tests/34-fn-builder-elided-lifetime.rs");
+    t.pass("tests/35-skip-computed.rs");
+    t.pass("tests/36-option-detection.rs");
 }